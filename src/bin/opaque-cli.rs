@@ -0,0 +1,564 @@
+// SPDX-FileCopyrightText: 2026 Digg - Agency for Digital Government
+//
+// SPDX-License-Identifier: EUPL-1.2
+
+//! Command-line tool for generating and inspecting OPAQUE artifacts.
+//!
+//! Binary inputs are given as hex, or as `@<file>` to read raw bytes from a file.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use clap::{Parser, Subcommand, ValueEnum};
+use opaque_ke_uniffi::{
+    client_driver::{
+        run_login, run_registration, session_key_fingerprint, DriverConfig, DriverError,
+        DriverStep, LocalServer, StepTiming, Transport,
+    },
+    client_login::{client_login_finish, client_login_start},
+    client_registration::{client_registration_finish, client_registration_start},
+    common::IdentityMode,
+    inspect::{describe_message, MessageDescription, MessageKind},
+    server::{server_public_key, server_setup},
+    server_login::{server_login_finish, server_login_start},
+    server_registration::{server_registration_finish, server_registration_start},
+};
+use serde_json::{json, Value};
+use std::{
+    error::Error,
+    sync::{Arc, Mutex},
+};
+
+#[derive(Parser)]
+#[command(name = "opaque-cli", about = "Generate and inspect OPAQUE artifacts")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Generate a new server setup
+    Setup {
+        /// Write the raw server setup to this file instead of printing it as hex
+        #[arg(long)]
+        out: Option<String>,
+    },
+    /// Print the server's static public key from a server setup
+    PublicKey {
+        /// Server setup, as hex or @<file>
+        setup: String,
+    },
+    /// Decode a protocol message, server setup or password file into its fields
+    Decode {
+        /// Kind of artifact to decode
+        kind: Kind,
+        /// Artifact, as hex or @<file>
+        message: String,
+    },
+    /// Run a full registration and login locally, printing every message
+    RoundTrip {
+        #[arg(long)]
+        password: String,
+        #[arg(long, default_value = "user")]
+        credential_identifier: String,
+        /// Client identity, given together with --server-identifier; the public keys are
+        /// used as identities if both are omitted
+        #[arg(long)]
+        client_identifier: Option<String>,
+        #[arg(long)]
+        server_identifier: Option<String>,
+        #[arg(long, default_value = "")]
+        context: String,
+        /// Server setup, as hex or @<file>; a new one is generated if omitted
+        #[arg(long)]
+        setup: Option<String>,
+    },
+    /// Register and log in with the client driver, reporting per-step timings
+    Drive {
+        #[arg(long)]
+        password: String,
+        #[arg(long, default_value = "user")]
+        credential_identifier: String,
+        /// Client identity, given together with --server-identifier; the public keys are
+        /// used as identities if both are omitted
+        #[arg(long)]
+        client_identifier: Option<String>,
+        #[arg(long)]
+        server_identifier: Option<String>,
+        #[arg(long, default_value = "")]
+        context: String,
+        /// Base URL of an opaque-server; an in-process server is used if omitted
+        #[arg(long)]
+        url: Option<String>,
+        /// Only log in, for an identifier registered earlier
+        #[arg(long)]
+        skip_registration: bool,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Kind {
+    ServerSetup,
+    RegistrationRequest,
+    RegistrationResponse,
+    RegistrationUpload,
+    PasswordFile,
+    CredentialRequest,
+    CredentialResponse,
+    CredentialFinalization,
+}
+
+impl From<Kind> for MessageKind {
+    fn from(kind: Kind) -> Self {
+        match kind {
+            Kind::ServerSetup => MessageKind::ServerSetup,
+            Kind::RegistrationRequest => MessageKind::RegistrationRequest,
+            Kind::RegistrationResponse => MessageKind::RegistrationResponse,
+            Kind::RegistrationUpload => MessageKind::RegistrationUpload,
+            Kind::PasswordFile => MessageKind::PasswordFile,
+            Kind::CredentialRequest => MessageKind::CredentialRequest,
+            Kind::CredentialResponse => MessageKind::CredentialResponse,
+            Kind::CredentialFinalization => MessageKind::CredentialFinalization,
+        }
+    }
+}
+
+/// Reads a binary input given as hex, or as `@<file>` for raw bytes.
+fn read_input(input: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    match input.strip_prefix('@') {
+        Some(path) => Ok(std::fs::read(path)?),
+        None => Ok(hex::decode(input.trim())?),
+    }
+}
+
+fn print_description(description: &MessageDescription) {
+    println!("kind:   {:?}", description.kind);
+    println!("suite:  {}", description.suite);
+    println!("length: {}", description.length);
+    for field in &description.fields {
+        println!(
+            "  {:<24} [{:>3}..{:>3}] {}",
+            field.name,
+            field.offset,
+            field.offset + field.length,
+            field
+                .value
+                .as_ref()
+                .map_or_else(|| "(secret)".to_string(), hex::encode)
+        );
+    }
+}
+
+fn print_message(kind: MessageKind, message: &[u8]) -> Result<(), Box<dyn Error>> {
+    print_description(&describe_message(kind, message.to_vec())?);
+    println!();
+    Ok(())
+}
+
+fn identity_mode(
+    client_identifier: Option<String>,
+    server_identifier: Option<String>,
+) -> Result<IdentityMode, Box<dyn Error>> {
+    match (client_identifier, server_identifier) {
+        (Some(client), Some(server)) => Ok(IdentityMode::Explicit {
+            client: client.into_bytes(),
+            server: server.into_bytes(),
+        }),
+        (None, None) => Ok(IdentityMode::DefaultPublicKeys),
+        _ => Err("--client-identifier and --server-identifier must be given together".into()),
+    }
+}
+
+fn round_trip(
+    password: String,
+    credential_identifier: String,
+    client_identifier: Option<String>,
+    server_identifier: Option<String>,
+    context: String,
+    setup: Option<String>,
+) -> Result<(), Box<dyn Error>> {
+    let password = password.into_bytes();
+    let credential_identifier = credential_identifier.into_bytes();
+    let identity_mode = identity_mode(client_identifier, server_identifier)?;
+    let context = context.into_bytes();
+    let setup = match setup {
+        Some(setup) => read_input(&setup)?,
+        None => server_setup(),
+    };
+
+    let reg_start = client_registration_start(password.clone())?;
+    print_message(
+        MessageKind::RegistrationRequest,
+        &reg_start.registration_request,
+    )?;
+
+    let registration_response = server_registration_start(
+        setup.clone(),
+        reg_start.registration_request,
+        &credential_identifier,
+    )?;
+    print_message(MessageKind::RegistrationResponse, &registration_response)?;
+
+    let reg_finish = client_registration_finish(
+        password.clone(),
+        reg_start.client_registration,
+        registration_response,
+        identity_mode.clone(),
+    )?;
+    print_message(
+        MessageKind::RegistrationUpload,
+        &reg_finish.registration_upload,
+    )?;
+
+    let password_file = server_registration_finish(reg_finish.registration_upload)?;
+
+    let login_start = client_login_start(password.clone())?;
+    print_message(
+        MessageKind::CredentialRequest,
+        &login_start.credential_request,
+    )?;
+
+    let server_start = server_login_start(
+        setup.clone(),
+        password_file,
+        login_start.credential_request,
+        &credential_identifier,
+        Some(context.clone()),
+        identity_mode.clone(),
+    )?;
+    print_message(
+        MessageKind::CredentialResponse,
+        &server_start.credential_response,
+    )?;
+
+    let login_finish = client_login_finish(
+        server_start.credential_response,
+        login_start.client_registration,
+        password,
+        context.clone(),
+        identity_mode.clone(),
+    )?;
+    print_message(
+        MessageKind::CredentialFinalization,
+        &login_finish.credential_finalization,
+    )?;
+
+    let server_session_key = server_login_finish(
+        server_start.server_login,
+        login_finish.credential_finalization,
+        Some(context),
+        identity_mode,
+    )?;
+
+    println!("export key:  {}", hex::encode(&reg_finish.export_key));
+    println!("session key: {}", hex::encode(&login_finish.session_key));
+    if server_session_key != login_finish.session_key {
+        return Err("Client and server session keys differ".into());
+    }
+    println!("Session keys match");
+
+    Ok(())
+}
+
+/// Transport to an `opaque-server` over its REST/JSON API.
+struct HttpTransport {
+    base_url: String,
+    config: DriverConfig,
+    /// The `registration_id` or `login_id` of the flow in progress.
+    flow_id: Mutex<Option<Value>>,
+}
+
+impl HttpTransport {
+    fn post(&self, path: &str, body: Value) -> Result<Value, DriverError> {
+        let url = format!("{}{}", self.base_url.trim_end_matches('/'), path);
+        let response = ureq::post(&url).send_json(body).map_err(|e| match e {
+            ureq::Error::Status(status, response) => DriverError::Transport(format!(
+                "{} returned {}: {}",
+                path,
+                status,
+                response.into_string().unwrap_or_default()
+            )),
+            e => DriverError::Transport(e.to_string()),
+        })?;
+
+        response
+            .into_json()
+            .map_err(|e| DriverError::Transport(e.to_string()))
+    }
+
+    fn field(value: &Value, name: &str) -> Result<Vec<u8>, DriverError> {
+        value[name]
+            .as_str()
+            .and_then(|field| STANDARD.decode(field).ok())
+            .ok_or_else(|| DriverError::Transport(format!("Missing field {}", name)))
+    }
+}
+
+impl Transport for HttpTransport {
+    fn exchange(
+        &self,
+        step: DriverStep,
+        credential_identifier: Vec<u8>,
+        message: Vec<u8>,
+    ) -> Result<Vec<u8>, DriverError> {
+        let credential_identifier = STANDARD.encode(credential_identifier);
+        let message = STANDARD.encode(message);
+        let mut flow_id = self
+            .flow_id
+            .lock()
+            .map_err(|e| DriverError::Transport(format!("{:?}", e)))?;
+
+        match step {
+            DriverStep::RegistrationStart => {
+                let response = self.post(
+                    "/registration/start",
+                    json!({
+                        "credential_identifier": credential_identifier,
+                        "registration_request": message,
+                    }),
+                )?;
+                *flow_id = Some(response["registration_id"].clone());
+                Self::field(&response, "registration_response")
+            }
+            DriverStep::RegistrationFinish => {
+                self.post(
+                    "/registration/finish",
+                    json!({
+                        "registration_id": flow_id.take(),
+                        "registration_upload": message,
+                    }),
+                )?;
+                Ok(Vec::new())
+            }
+            DriverStep::LoginStart => {
+                let mut body = json!({
+                    "credential_identifier": credential_identifier,
+                    "credential_request": message,
+                    "context": STANDARD.encode(&self.config.context),
+                });
+                if let IdentityMode::Explicit { client, server } = &self.config.identity_mode {
+                    body["client_identifier"] = STANDARD.encode(client).into();
+                    body["server_identifier"] = STANDARD.encode(server).into();
+                }
+                let response = self.post("/login/start", body)?;
+                *flow_id = Some(response["login_id"].clone());
+                Self::field(&response, "credential_response")
+            }
+            DriverStep::LoginFinish => {
+                self.post(
+                    "/login/finish",
+                    json!({
+                        "login_id": flow_id.take(),
+                        "credential_finalization": message,
+                    }),
+                )?;
+                Ok(Vec::new())
+            }
+        }
+    }
+}
+
+fn print_timings(steps: &[StepTiming]) {
+    for timing in steps {
+        println!(
+            "  {:<20} client {:>8} µs  transport {:>8} µs",
+            format!("{:?}", timing.step),
+            timing.client_micros,
+            timing.transport_micros
+        );
+    }
+}
+
+fn drive(
+    password: String,
+    credential_identifier: String,
+    config: DriverConfig,
+    url: Option<String>,
+    skip_registration: bool,
+) -> Result<(), Box<dyn Error>> {
+    let password = password.into_bytes();
+    let credential_identifier = credential_identifier.into_bytes();
+    let (transport, local_server): (Arc<dyn Transport>, _) = match url {
+        Some(base_url) => (
+            Arc::new(HttpTransport {
+                base_url,
+                config: config.clone(),
+                flow_id: Mutex::new(None),
+            }),
+            None,
+        ),
+        None => {
+            let server = Arc::new(LocalServer::new(server_setup(), config.clone()));
+            (server.clone(), Some(server))
+        }
+    };
+
+    if !skip_registration {
+        let registration = run_registration(
+            transport.clone(),
+            credential_identifier.clone(),
+            password.clone(),
+            config.clone(),
+        )?;
+        println!("Registration");
+        print_timings(&registration.steps);
+    }
+
+    let login = run_login(transport, credential_identifier.clone(), password, config)?;
+    println!("Login");
+    print_timings(&login.steps);
+    println!("session key fingerprint: {}", login.session_key_fingerprint);
+
+    if let Some(session_key) = local_server.and_then(|s| s.session_key(&credential_identifier)) {
+        println!(
+            "server fingerprint:      {}",
+            session_key_fingerprint(&session_key)
+        );
+    }
+
+    Ok(())
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    match Cli::parse().command {
+        Command::Setup { out } => {
+            let setup = server_setup();
+            match out {
+                Some(path) => std::fs::write(path, setup)?,
+                None => println!("{}", hex::encode(setup)),
+            }
+        }
+        Command::PublicKey { setup } => {
+            println!("{}", hex::encode(server_public_key(read_input(&setup)?)?));
+        }
+        Command::Decode { kind, message } => {
+            print_description(&describe_message(kind.into(), read_input(&message)?)?);
+        }
+        Command::RoundTrip {
+            password,
+            credential_identifier,
+            client_identifier,
+            server_identifier,
+            context,
+            setup,
+        } => round_trip(
+            password,
+            credential_identifier,
+            client_identifier,
+            server_identifier,
+            context,
+            setup,
+        )?,
+        Command::Drive {
+            password,
+            credential_identifier,
+            client_identifier,
+            server_identifier,
+            context,
+            url,
+            skip_registration,
+        } => drive(
+            password,
+            credential_identifier,
+            DriverConfig {
+                context: context.into_bytes(),
+                identity_mode: identity_mode(client_identifier, server_identifier)?,
+            },
+            url,
+            skip_registration,
+        )?,
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parses the identity flags of a `drive` command line into its identity mode.
+    fn drive_identity_mode(args: &[&str]) -> Result<IdentityMode, Box<dyn Error>> {
+        let cli = Cli::try_parse_from(
+            ["opaque-cli", "drive", "--password", "password"]
+                .iter()
+                .chain(args),
+        )?;
+        match cli.command {
+            Command::Drive {
+                client_identifier,
+                server_identifier,
+                ..
+            } => identity_mode(client_identifier, server_identifier),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn read_input_accepts_hex_and_files() {
+        assert_eq!(read_input("0aff").unwrap(), [0x0a, 0xff]);
+        assert_eq!(read_input(" 0aff\n").unwrap(), [0x0a, 0xff]);
+        assert!(read_input("0afg").is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("setup.bin");
+        std::fs::write(&path, [0u8, 1, 2]).unwrap();
+        assert_eq!(
+            read_input(&format!("@{}", path.display())).unwrap(),
+            [0, 1, 2]
+        );
+        assert!(read_input(&format!("@{}", dir.path().join("missing").display())).is_err());
+    }
+
+    #[test]
+    fn drive_identity_flags() {
+        assert_eq!(
+            drive_identity_mode(&[]).unwrap(),
+            IdentityMode::DefaultPublicKeys
+        );
+        assert_eq!(
+            drive_identity_mode(&["--client-identifier", "alice", "--server-identifier", "srv"])
+                .unwrap(),
+            IdentityMode::Explicit {
+                client: b"alice".to_vec(),
+                server: b"srv".to_vec(),
+            }
+        );
+        assert!(drive_identity_mode(&["--client-identifier", "alice"]).is_err());
+    }
+
+    #[test]
+    fn drive_against_local_server_is_ok() {
+        for identity_mode in [
+            IdentityMode::DefaultPublicKeys,
+            IdentityMode::Explicit {
+                client: b"client".to_vec(),
+                server: b"server".to_vec(),
+            },
+        ] {
+            let config = DriverConfig {
+                context: b"context".to_vec(),
+                identity_mode,
+            };
+            drive("password".into(), "user".into(), config, None, false).unwrap();
+        }
+    }
+
+    #[test]
+    fn http_transport_reports_unreachable_server() {
+        let transport = HttpTransport {
+            base_url: "http://127.0.0.1:1/".into(),
+            config: DriverConfig {
+                context: Vec::new(),
+                identity_mode: IdentityMode::DefaultPublicKeys,
+            },
+            flow_id: Mutex::new(None),
+        };
+        assert!(matches!(
+            transport.exchange(
+                DriverStep::RegistrationStart,
+                b"user".to_vec(),
+                b"request".to_vec()
+            ),
+            Err(DriverError::Transport(_))
+        ));
+    }
+}
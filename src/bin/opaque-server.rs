@@ -0,0 +1,679 @@
+// SPDX-FileCopyrightText: 2026 Digg - Agency for Digital Government
+//
+// SPDX-License-Identifier: EUPL-1.2
+
+//! A reference OPAQUE server exposing the server functions over a small REST/JSON API,
+//! meant for testing the generated bindings end to end against a local server.
+//!
+//! All binary fields are standard base64. Endpoints (all `POST`):
+//!
+//! | Path                     | Request                                              | Response                           |
+//! |--------------------------|------------------------------------------------------|------------------------------------|
+//! | `/registration/start`    | `credential_identifier`, `registration_request`      | `registration_id`, `registration_response` |
+//! | `/registration/finish`   | `registration_id`, `registration_upload`             | `{}`                               |
+//! | `/login/start`           | `credential_identifier`, `credential_request`, optional `context`, `client_identifier` and `server_identifier` (both or neither), optional `one_time` | `login_id`, `credential_response` |
+//! | `/login/finish`          | `login_id`, `credential_finalization`                | `session_key`                      |
+//!
+//! The session key is returned by `/login/finish` so that it can be compared with the
+//! client's; a real server must never do this.
+//!
+//! A login started with `"one_time": true`, e.g. with a recovery code, deletes the password
+//! file when it finishes, and only succeeds if it was the one to delete it: of concurrent
+//! logins with the same code, one succeeds and the others fail with 401.
+//!
+//! A registration or login must be finished within 60 seconds of its start, and at most
+//! 10000 of each can be pending at once; further starts are rejected with 503 until some
+//! finish or expire.
+//!
+//! Usage: `opaque-server [--addr 127.0.0.1:8080] [--setup <file>] [--db <file>]`
+//!
+//! Without `--setup` a new server setup is generated on every start, and without `--db`
+//! password files are only kept in memory.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use opaque_ke_uniffi::{
+    common::IdentityMode,
+    server::server_setup,
+    server_login::{server_login_finish, server_login_start},
+    server_registration::{server_registration_finish, server_registration_start},
+    sqlite_store::{CredentialStoreError, SqliteCredentialStore},
+};
+use rand::{rngs::OsRng, RngCore};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tiny_http::{Header, Method, Response, Server};
+
+/// How long a started registration or login can be finished.
+const PENDING_TTL: Duration = Duration::from_secs(60);
+/// The most registrations, and separately logins, that can be pending at once.
+const MAX_PENDING: usize = 10_000;
+
+#[derive(Deserialize)]
+struct RegistrationStartRequest {
+    credential_identifier: String,
+    registration_request: String,
+}
+
+#[derive(Serialize)]
+struct RegistrationStartResponse {
+    registration_id: String,
+    registration_response: String,
+}
+
+#[derive(Deserialize)]
+struct RegistrationFinishRequest {
+    registration_id: String,
+    registration_upload: String,
+}
+
+#[derive(Deserialize)]
+struct LoginStartRequest {
+    credential_identifier: String,
+    credential_request: String,
+    context: Option<String>,
+    client_identifier: Option<String>,
+    server_identifier: Option<String>,
+    #[serde(default)]
+    one_time: bool,
+}
+
+#[derive(Serialize)]
+struct LoginStartResponse {
+    login_id: String,
+    credential_response: String,
+}
+
+#[derive(Deserialize)]
+struct LoginFinishRequest {
+    login_id: String,
+    credential_finalization: String,
+}
+
+#[derive(Serialize)]
+struct LoginFinishResponse {
+    session_key: String,
+}
+
+#[derive(Serialize)]
+struct ErrorResponse {
+    error: String,
+}
+
+/// An HTTP status code and a message, rendered as an `ErrorResponse`.
+#[derive(Debug)]
+struct HttpError(u16, String);
+
+enum PasswordFileStore {
+    Memory(Mutex<HashMap<Vec<u8>, Vec<u8>>>),
+    Sqlite(Arc<SqliteCredentialStore>),
+}
+
+impl PasswordFileStore {
+    fn insert(
+        &self,
+        credential_identifier: Vec<u8>,
+        password_file: Vec<u8>,
+    ) -> Result<(), HttpError> {
+        match self {
+            PasswordFileStore::Memory(files) => {
+                let mut files = files.lock().map_err(internal)?;
+                if files.contains_key(&credential_identifier) {
+                    return Err(HttpError(
+                        409,
+                        "Credential identifier already registered".into(),
+                    ));
+                }
+                files.insert(credential_identifier, password_file);
+                Ok(())
+            }
+            PasswordFileStore::Sqlite(store) => store
+                .insert(credential_identifier, password_file)
+                .map_err(|e| match e {
+                    CredentialStoreError::AlreadyExists => HttpError(409, e.to_string()),
+                    CredentialStoreError::InvalidPasswordFile(_) => HttpError(400, e.to_string()),
+                    _ => internal(e),
+                }),
+        }
+    }
+
+    fn get(&self, credential_identifier: &[u8]) -> Result<Option<Vec<u8>>, HttpError> {
+        match self {
+            PasswordFileStore::Memory(files) => Ok(files
+                .lock()
+                .map_err(internal)?
+                .get(credential_identifier)
+                .cloned()),
+            PasswordFileStore::Sqlite(store) => {
+                store.get(credential_identifier.to_vec()).map_err(internal)
+            }
+        }
+    }
+
+    /// Deletes the password file of a one-time credential, returning whether this call
+    /// deleted it.
+    fn consume(&self, credential_identifier: Vec<u8>) -> Result<bool, HttpError> {
+        match self {
+            PasswordFileStore::Memory(files) => Ok(files
+                .lock()
+                .map_err(internal)?
+                .remove(&credential_identifier)
+                .is_some()),
+            PasswordFileStore::Sqlite(store) => match store.consume(credential_identifier) {
+                Ok(()) => Ok(true),
+                Err(CredentialStoreError::NotFound) => Ok(false),
+                Err(e) => Err(internal(e)),
+            },
+        }
+    }
+}
+
+/// Flows started but not yet finished, keyed by a random ID, that expire after a TTL.
+struct PendingFlows<T> {
+    flows: Mutex<HashMap<String, (Instant, T)>>,
+    ttl: Duration,
+    capacity: usize,
+}
+
+impl<T> PendingFlows<T> {
+    fn new(ttl: Duration, capacity: usize) -> Self {
+        Self {
+            flows: Mutex::new(HashMap::new()),
+            ttl,
+            capacity,
+        }
+    }
+
+    /// Stores a started flow, returning its ID.
+    fn insert(&self, flow: T) -> Result<String, HttpError> {
+        let mut flows = self.flows.lock().map_err(internal)?;
+        if flows.len() >= self.capacity {
+            flows.retain(|_, (started, _)| started.elapsed() < self.ttl);
+            if flows.len() >= self.capacity {
+                return Err(HttpError(503, "Too many pending flows".into()));
+            }
+        }
+
+        let mut id = [0u8; 16];
+        OsRng.fill_bytes(&mut id);
+        let id = STANDARD.encode(id);
+        flows.insert(id.clone(), (Instant::now(), flow));
+        Ok(id)
+    }
+
+    /// Removes a started flow, failing if it is unknown or expired.
+    fn take(&self, id: &str) -> Result<T, HttpError> {
+        match self.flows.lock().map_err(internal)?.remove(id) {
+            Some((started, flow)) if started.elapsed() < self.ttl => Ok(flow),
+            _ => Err(HttpError(404, "Unknown or expired flow ID".into())),
+        }
+    }
+}
+
+/// Server login state kept between `/login/start` and `/login/finish`.
+struct PendingLogin {
+    server_login: Vec<u8>,
+    context: Option<Vec<u8>>,
+    identity_mode: IdentityMode,
+    /// The credential identifier of a one-time login, consumed when it finishes.
+    one_time_credential: Option<Vec<u8>>,
+}
+
+struct AppState {
+    server_setup: Vec<u8>,
+    store: PasswordFileStore,
+    /// The credential identifiers of registrations between start and finish.
+    pending_registrations: PendingFlows<Vec<u8>>,
+    pending_logins: PendingFlows<PendingLogin>,
+}
+
+impl AppState {
+    fn new(server_setup: Vec<u8>, store: PasswordFileStore) -> Self {
+        Self {
+            server_setup,
+            store,
+            pending_registrations: PendingFlows::new(PENDING_TTL, MAX_PENDING),
+            pending_logins: PendingFlows::new(PENDING_TTL, MAX_PENDING),
+        }
+    }
+
+    fn handle(&self, method: &Method, path: &str, body: &str) -> Result<String, HttpError> {
+        if *method != Method::Post {
+            return Err(HttpError(405, "Only POST is supported".into()));
+        }
+
+        match path {
+            "/registration/start" => self.registration_start(parse(body)?),
+            "/registration/finish" => self.registration_finish(parse(body)?),
+            "/login/start" => self.login_start(parse(body)?),
+            "/login/finish" => self.login_finish(parse(body)?),
+            _ => Err(HttpError(404, format!("Unknown endpoint {}", path))),
+        }
+    }
+
+    fn registration_start(&self, request: RegistrationStartRequest) -> Result<String, HttpError> {
+        let credential_identifier = decode(&request.credential_identifier)?;
+
+        let registration_response = server_registration_start(
+            self.server_setup.clone(),
+            decode(&request.registration_request)?,
+            &credential_identifier,
+        )
+        .map_err(bad_request)?;
+        let registration_id = self.pending_registrations.insert(credential_identifier)?;
+
+        render(&RegistrationStartResponse {
+            registration_id,
+            registration_response: STANDARD.encode(registration_response),
+        })
+    }
+
+    fn registration_finish(&self, request: RegistrationFinishRequest) -> Result<String, HttpError> {
+        // The password file is stored under the identifier the registration was started for.
+        let credential_identifier = self.pending_registrations.take(&request.registration_id)?;
+        let password_file = server_registration_finish(decode(&request.registration_upload)?)
+            .map_err(bad_request)?;
+
+        self.store.insert(credential_identifier, password_file)?;
+
+        Ok("{}".into())
+    }
+
+    fn login_start(&self, request: LoginStartRequest) -> Result<String, HttpError> {
+        let credential_identifier = decode(&request.credential_identifier)?;
+        let context = request.context.as_deref().map(decode).transpose()?;
+        let identity_mode = match (&request.client_identifier, &request.server_identifier) {
+            (Some(client), Some(server)) => IdentityMode::Explicit {
+                client: decode(client)?,
+                server: decode(server)?,
+            },
+            (None, None) => IdentityMode::DefaultPublicKeys,
+            _ => {
+                return Err(HttpError(
+                    400,
+                    "client_identifier and server_identifier must be given together".into(),
+                ))
+            }
+        };
+
+        // An unknown identifier gets an empty password file, so the response is
+        // indistinguishable from that of a registered one.
+        let password_file = self.store.get(&credential_identifier)?.unwrap_or_default();
+
+        let result = server_login_start(
+            self.server_setup.clone(),
+            password_file,
+            decode(&request.credential_request)?,
+            &credential_identifier,
+            context.clone(),
+            identity_mode.clone(),
+        )
+        .map_err(bad_request)?;
+
+        let login_id = self.pending_logins.insert(PendingLogin {
+            server_login: result.server_login,
+            context,
+            identity_mode,
+            one_time_credential: request.one_time.then_some(credential_identifier),
+        })?;
+
+        render(&LoginStartResponse {
+            login_id,
+            credential_response: STANDARD.encode(result.credential_response),
+        })
+    }
+
+    fn login_finish(&self, request: LoginFinishRequest) -> Result<String, HttpError> {
+        let pending = self.pending_logins.take(&request.login_id)?;
+
+        let session_key = server_login_finish(
+            pending.server_login,
+            decode(&request.credential_finalization)?,
+            pending.context,
+            pending.identity_mode,
+        )
+        .map_err(|e| HttpError(401, e.to_string()))?;
+
+        if let Some(credential_identifier) = pending.one_time_credential {
+            if !self.store.consume(credential_identifier)? {
+                return Err(HttpError(
+                    401,
+                    "The one-time credential was already used".into(),
+                ));
+            }
+        }
+
+        render(&LoginFinishResponse {
+            session_key: STANDARD.encode(session_key),
+        })
+    }
+}
+
+fn parse<T: DeserializeOwned>(body: &str) -> Result<T, HttpError> {
+    serde_json::from_str(body).map_err(bad_request)
+}
+
+fn render<T: Serialize>(response: &T) -> Result<String, HttpError> {
+    serde_json::to_string(response).map_err(internal)
+}
+
+fn decode(value: &str) -> Result<Vec<u8>, HttpError> {
+    STANDARD.decode(value).map_err(bad_request)
+}
+
+fn bad_request<E: std::fmt::Display>(e: E) -> HttpError {
+    HttpError(400, e.to_string())
+}
+
+fn internal<E: std::fmt::Debug>(e: E) -> HttpError {
+    HttpError(500, format!("{:?}", e))
+}
+
+struct Args {
+    addr: String,
+    setup: Option<String>,
+    db: Option<String>,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = Args {
+        addr: "127.0.0.1:8080".into(),
+        setup: None,
+        db: None,
+    };
+
+    let mut iter = std::env::args().skip(1);
+    while let Some(flag) = iter.next() {
+        let mut value = || iter.next().ok_or(format!("Missing value for {}", flag));
+        match flag.as_str() {
+            "--addr" => args.addr = value()?,
+            "--setup" => args.setup = Some(value()?),
+            "--db" => args.db = Some(value()?),
+            _ => return Err(format!("Unknown argument {}", flag)),
+        }
+    }
+
+    Ok(args)
+}
+
+/// Loads the server setup from `path`, generating and saving a new one if the file is missing.
+fn load_or_create_setup(path: &str) -> std::io::Result<Vec<u8>> {
+    match std::fs::read(path) {
+        Ok(setup) => Ok(setup),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            let setup = server_setup();
+            std::fs::write(path, &setup)?;
+            Ok(setup)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let args = parse_args().map_err(|e| {
+        format!(
+            "{}\nUsage: opaque-server [--addr 127.0.0.1:8080] [--setup <file>] [--db <file>]",
+            e
+        )
+    })?;
+
+    let server_setup = match &args.setup {
+        Some(path) => load_or_create_setup(path)?,
+        None => server_setup(),
+    };
+
+    let store = match args.db {
+        Some(path) => PasswordFileStore::Sqlite(SqliteCredentialStore::open(path)?),
+        None => PasswordFileStore::Memory(Mutex::new(HashMap::new())),
+    };
+
+    let state = AppState::new(server_setup, store);
+
+    let server = Server::http(&args.addr)?;
+    println!("Listening on http://{}", args.addr);
+
+    let content_type = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..])
+        .expect("static header is valid");
+
+    for mut request in server.incoming_requests() {
+        let mut body = String::new();
+        let result = match request.as_reader().read_to_string(&mut body) {
+            Ok(_) => state.handle(request.method(), request.url(), &body),
+            Err(e) => Err(bad_request(e)),
+        };
+
+        let (status, body) = match result {
+            Ok(body) => (200, body),
+            Err(HttpError(status, error)) => (
+                status,
+                serde_json::to_string(&ErrorResponse { error }).unwrap_or_default(),
+            ),
+        };
+        println!("{} {} -> {}", request.method(), request.url(), status);
+
+        let response = Response::from_string(body)
+            .with_status_code(status)
+            .with_header(content_type.clone());
+        if let Err(e) = request.respond(response) {
+            eprintln!("Failed to send response: {}", e);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opaque_ke_uniffi::{
+        client_login::{client_login_finish, client_login_start},
+        client_registration::{client_registration_finish, client_registration_start},
+    };
+    use serde_json::{json, Value};
+
+    fn post(state: &AppState, path: &str, body: Value) -> Result<Value, u16> {
+        state
+            .handle(&Method::Post, path, &body.to_string())
+            .map(|response| serde_json::from_str(&response).unwrap())
+            .map_err(|HttpError(status, _)| status)
+    }
+
+    fn field(value: &Value, name: &str) -> Vec<u8> {
+        STANDARD.decode(value[name].as_str().unwrap()).unwrap()
+    }
+
+    fn memory_state() -> AppState {
+        AppState::new(
+            server_setup(),
+            PasswordFileStore::Memory(Mutex::new(HashMap::new())),
+        )
+    }
+
+    #[test]
+    fn full_flow_over_json_api() {
+        let state = memory_state();
+        let password = b"password".to_vec();
+        let credential_identifier = STANDARD.encode("alice");
+        let identity_mode = IdentityMode::Explicit {
+            client: b"client".to_vec(),
+            server: b"server".to_vec(),
+        };
+
+        let reg_start = client_registration_start(password.clone()).unwrap();
+        let response = post(
+            &state,
+            "/registration/start",
+            json!({
+                "credential_identifier": credential_identifier,
+                "registration_request": STANDARD.encode(&reg_start.registration_request),
+            }),
+        )
+        .unwrap();
+        let reg_finish = client_registration_finish(
+            password.clone(),
+            reg_start.client_registration,
+            field(&response, "registration_response"),
+            identity_mode.clone(),
+        )
+        .unwrap();
+        let upload = json!({
+            "registration_id": response["registration_id"],
+            "registration_upload": STANDARD.encode(&reg_finish.registration_upload),
+        });
+        post(&state, "/registration/finish", upload.clone()).unwrap();
+        assert_eq!(post(&state, "/registration/finish", upload), Err(404));
+
+        // The identifier is taken, even by a registration started for it afterwards
+        let response = post(
+            &state,
+            "/registration/start",
+            json!({
+                "credential_identifier": credential_identifier,
+                "registration_request": STANDARD.encode(reg_start.registration_request),
+            }),
+        )
+        .unwrap();
+        let upload = json!({
+            "registration_id": response["registration_id"],
+            "registration_upload": STANDARD.encode(&reg_finish.registration_upload),
+        });
+        assert_eq!(post(&state, "/registration/finish", upload), Err(409));
+
+        let login_start = client_login_start(password.clone()).unwrap();
+        let response = post(
+            &state,
+            "/login/start",
+            json!({
+                "credential_identifier": credential_identifier,
+                "credential_request": STANDARD.encode(login_start.credential_request),
+                "context": STANDARD.encode("context"),
+                "client_identifier": STANDARD.encode("client"),
+                "server_identifier": STANDARD.encode("server"),
+            }),
+        )
+        .unwrap();
+        let login_finish = client_login_finish(
+            field(&response, "credential_response"),
+            login_start.client_registration,
+            password,
+            b"context".to_vec(),
+            identity_mode,
+        )
+        .unwrap();
+        let finish_request = json!({
+            "login_id": response["login_id"],
+            "credential_finalization": STANDARD.encode(login_finish.credential_finalization),
+        });
+        let response = post(&state, "/login/finish", finish_request.clone()).unwrap();
+
+        assert_eq!(field(&response, "session_key"), login_finish.session_key);
+        assert_eq!(post(&state, "/login/finish", finish_request), Err(404));
+    }
+
+    #[test]
+    fn one_time_login_succeeds_once() {
+        let state = memory_state();
+        let password = b"recovery code".to_vec();
+        let credential_identifier = STANDARD.encode("alice-recovery");
+
+        let reg_start = client_registration_start(password.clone()).unwrap();
+        let response = post(
+            &state,
+            "/registration/start",
+            json!({
+                "credential_identifier": credential_identifier,
+                "registration_request": STANDARD.encode(reg_start.registration_request),
+            }),
+        )
+        .unwrap();
+        let reg_finish = client_registration_finish(
+            password.clone(),
+            reg_start.client_registration,
+            field(&response, "registration_response"),
+            IdentityMode::DefaultPublicKeys,
+        )
+        .unwrap();
+        post(
+            &state,
+            "/registration/finish",
+            json!({
+                "registration_id": response["registration_id"],
+                "registration_upload": STANDARD.encode(reg_finish.registration_upload),
+            }),
+        )
+        .unwrap();
+
+        // Starts a one-time login, returning the client's finish request if the client
+        // could finish it
+        let login = || {
+            let login_start = client_login_start(password.clone()).unwrap();
+            let response = post(
+                &state,
+                "/login/start",
+                json!({
+                    "credential_identifier": credential_identifier,
+                    "credential_request": STANDARD.encode(login_start.credential_request),
+                    "one_time": true,
+                }),
+            )
+            .unwrap();
+            let login_finish = client_login_finish(
+                field(&response, "credential_response"),
+                login_start.client_registration,
+                password.clone(),
+                Vec::new(),
+                IdentityMode::DefaultPublicKeys,
+            )
+            .ok()?;
+            Some(json!({
+                "login_id": response["login_id"],
+                "credential_finalization": STANDARD.encode(login_finish.credential_finalization),
+            }))
+        };
+
+        // Of two concurrent logins with the code, only the first to finish succeeds
+        let first = login().unwrap();
+        let second = login().unwrap();
+        assert!(post(&state, "/login/finish", second).is_ok());
+        assert_eq!(post(&state, "/login/finish", first), Err(401));
+
+        // The password file is gone, so the code no longer logs in
+        assert!(login().is_none());
+    }
+
+    #[test]
+    fn malformed_requests_are_rejected() {
+        let state = memory_state();
+
+        assert_eq!(post(&state, "/unknown", json!({})), Err(404));
+        assert_eq!(post(&state, "/login/start", json!({})), Err(400));
+        assert_eq!(
+            post(
+                &state,
+                "/registration/start",
+                json!({ "credential_identifier": "not base64!", "registration_request": "" }),
+            ),
+            Err(400)
+        );
+    }
+
+    #[test]
+    fn pending_flows_expire_and_are_capped() {
+        let flows = PendingFlows::new(PENDING_TTL, 2);
+        let first = flows.insert(1).unwrap();
+        flows.insert(2).unwrap();
+        assert!(matches!(flows.insert(3), Err(HttpError(503, _))));
+        assert_eq!(flows.take(&first).unwrap(), 1);
+        assert!(matches!(flows.take(&first), Err(HttpError(404, _))));
+        flows.insert(3).unwrap();
+
+        // Expired flows cannot be finished and make room for new ones
+        let flows = PendingFlows::new(Duration::ZERO, 1);
+        let expired = flows.insert(1).unwrap();
+        flows.insert(2).unwrap();
+        assert!(matches!(flows.take(&expired), Err(HttpError(404, _))));
+    }
+}
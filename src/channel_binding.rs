@@ -0,0 +1,128 @@
+// SPDX-FileCopyrightText: 2026 Digg - Agency for Digital Government
+//
+// SPDX-License-Identifier: EUPL-1.2
+
+//! This module binds an OPAQUE login to the TLS session it runs over.
+//!
+//! Both sides export keying material from their TLS connection (RFC 5705, RFC 8446
+//! Section 7.5) with the same exporter label and encode it, together with application
+//! labels, into the `context` passed to `server_login_start`, `client_login_finish` and
+//! `server_login_finish`. The context is part of the OPAQUE transcript, so the key
+//! confirmation MACs of the login only verify if both sides saw the same TLS session: a
+//! login relayed through a TLS-terminating attacker fails instead of yielding a session key.
+
+use crate::common::push_length_prefixed;
+
+/// Prefix of every encoded channel binding context, identifying its format.
+const CHANNEL_BINDING_TAG: &[u8] = b"OPAQUE-TLS-Channel-Binding-v1";
+
+#[derive(Debug, thiserror::Error, uniffi::Error)]
+pub enum ChannelBindingError {
+    #[error("Invalid parameter: {0}")]
+    InvalidParameter(String),
+}
+
+/// Encodes a TLS exporter value and application labels into an OPAQUE login context.
+///
+/// `exporter_label` is the label the exporter value was derived with, e.g.
+/// `EXPORTER-Channel-Binding` (RFC 9266), and `exporter_value` the exported keying
+/// material. `application_labels` distinguish logins of different applications or
+/// purposes over the same connection; their order is significant.
+///
+/// The encoding is the format tag followed by the exporter label, the exporter value,
+/// the number of application labels and the labels themselves, each with a 2-byte
+/// big-endian length prefix, so distinct inputs never encode to the same context.
+/// The client and the server must call this with the same arguments.
+#[uniffi::export]
+pub fn channel_binding_context(
+    exporter_label: Vec<u8>,
+    exporter_value: Vec<u8>,
+    application_labels: Vec<Vec<u8>>,
+) -> Result<Vec<u8>, ChannelBindingError> {
+    if exporter_label.is_empty() {
+        return Err(ChannelBindingError::InvalidParameter(
+            "the exporter label is empty".into(),
+        ));
+    }
+    if exporter_value.is_empty() {
+        return Err(ChannelBindingError::InvalidParameter(
+            "the exporter value is empty".into(),
+        ));
+    }
+    let label_count = u16::try_from(application_labels.len())
+        .map_err(|_| ChannelBindingError::InvalidParameter("too many application labels".into()))?;
+
+    let mut context = CHANNEL_BINDING_TAG.to_vec();
+    push_length_prefixed(&mut context, "the exporter label", &exporter_label)
+        .map_err(ChannelBindingError::InvalidParameter)?;
+    push_length_prefixed(&mut context, "the exporter value", &exporter_value)
+        .map_err(ChannelBindingError::InvalidParameter)?;
+    context.extend_from_slice(&label_count.to_be_bytes());
+    for label in &application_labels {
+        push_length_prefixed(&mut context, "an application label", label)
+            .map_err(ChannelBindingError::InvalidParameter)?;
+    }
+
+    // OPAQUE limits the context to a 2-byte length.
+    if context.len() > u16::MAX as usize {
+        return Err(ChannelBindingError::InvalidParameter(
+            "the encoded context is longer than 65535 bytes".into(),
+        ));
+    }
+    Ok(context)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LABEL: &[u8] = b"EXPORTER-Channel-Binding";
+
+    fn context(value: &[u8], labels: &[&[u8]]) -> Vec<u8> {
+        channel_binding_context(
+            LABEL.to_vec(),
+            value.to_vec(),
+            labels.iter().map(|l| l.to_vec()).collect(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_encoding() {
+        let mut expected = CHANNEL_BINDING_TAG.to_vec();
+        expected.extend_from_slice(&[0, 24]);
+        expected.extend_from_slice(LABEL);
+        expected.extend_from_slice(&[0, 2, 0xab, 0xcd]);
+        expected.extend_from_slice(&[0, 1, 0, 3]);
+        expected.extend_from_slice(b"app");
+        assert_eq!(context(&[0xab, 0xcd], &[b"app"]), expected);
+    }
+
+    #[test]
+    fn test_distinct_inputs_give_distinct_contexts() {
+        let contexts = [
+            context(b"value", &[]),
+            context(b"value", &[b""]),
+            context(b"value", &[b"ab"]),
+            context(b"value", &[b"a", b"b"]),
+            context(b"value", &[b"b", b"a"]),
+            context(b"valu", &[b"e"]),
+            context(b"other", &[b"ab"]),
+        ];
+        for (i, a) in contexts.iter().enumerate() {
+            for b in &contexts[i + 1..] {
+                assert_ne!(a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_parameters_are_rejected() {
+        assert!(channel_binding_context(vec![], b"value".to_vec(), vec![]).is_err());
+        assert!(channel_binding_context(LABEL.to_vec(), vec![], vec![]).is_err());
+        assert!(channel_binding_context(LABEL.to_vec(), vec![0; 65536], vec![]).is_err());
+        assert!(
+            channel_binding_context(LABEL.to_vec(), vec![0; 32], vec![vec![0; 40000]; 2]).is_err()
+        );
+    }
+}
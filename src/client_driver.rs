@@ -0,0 +1,318 @@
+// SPDX-FileCopyrightText: 2026 Digg - Agency for Digital Government
+//
+// SPDX-License-Identifier: EUPL-1.2
+
+//! This module drives the full client side of registration and login against a server
+//! reached through a caller-provided transport, for smoke-testing deployments.
+
+use crate::{
+    client_login::{client_login_finish, client_login_start},
+    client_registration::{client_registration_finish, client_registration_start},
+    common::IdentityMode,
+    server_login::{server_login_finish, server_login_start},
+    server_registration::{server_registration_finish, server_registration_start},
+};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// The protocol steps a message is sent for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, uniffi::Enum)]
+pub enum DriverStep {
+    /// Sends the RegistrationRequest, expects the RegistrationResponse.
+    RegistrationStart,
+    /// Sends the RegistrationUpload, the reply is ignored.
+    RegistrationFinish,
+    /// Sends the CredentialRequest, expects the CredentialResponse.
+    LoginStart,
+    /// Sends the CredentialFinalization, the reply is ignored.
+    LoginFinish,
+}
+
+#[derive(Debug, thiserror::Error, uniffi::Error)]
+pub enum DriverError {
+    #[error("Transport failed: {0}")]
+    Transport(String),
+    #[error("Registration failed: {0}")]
+    Registration(String),
+    #[error("Login failed: {0}")]
+    Login(String),
+}
+
+impl From<uniffi::UnexpectedUniFFICallbackError> for DriverError {
+    fn from(e: uniffi::UnexpectedUniFFICallbackError) -> Self {
+        DriverError::Transport(e.reason)
+    }
+}
+
+/// Carries protocol messages to a server and returns its replies.
+#[uniffi::export(with_foreign)]
+pub trait Transport: Send + Sync {
+    /// Sends `message` for `step` of the flow of `credential_identifier`, returning the reply.
+    fn exchange(
+        &self,
+        step: DriverStep,
+        credential_identifier: Vec<u8>,
+        message: Vec<u8>,
+    ) -> Result<Vec<u8>, DriverError>;
+}
+
+/// Context and identities used by both the client and the server side.
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct DriverConfig {
+    pub context: Vec<u8>,
+    pub identity_mode: IdentityMode,
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct StepTiming {
+    pub step: DriverStep,
+    /// Time spent computing the client side of the step.
+    pub client_micros: u64,
+    /// Time spent waiting for the transport.
+    pub transport_micros: u64,
+}
+
+#[derive(uniffi::Record)]
+pub struct RegistrationReport {
+    pub steps: Vec<StepTiming>,
+    pub export_key: Vec<u8>,
+}
+
+#[derive(uniffi::Record)]
+pub struct LoginReport {
+    pub steps: Vec<StepTiming>,
+    pub export_key: Vec<u8>,
+    /// Hex encoded, truncated SHA-256 of the session key, safe to log and compare.
+    pub session_key_fingerprint: String,
+}
+
+/// Runs a full registration for `credential_identifier` through `transport`.
+#[uniffi::export]
+pub fn run_registration(
+    transport: Arc<dyn Transport>,
+    credential_identifier: Vec<u8>,
+    password: Vec<u8>,
+    config: DriverConfig,
+) -> Result<RegistrationReport, DriverError> {
+    let mut steps = Vec::new();
+
+    let client_start = Instant::now();
+    let start = client_registration_start(password.clone())
+        .map_err(|e| DriverError::Registration(e.to_string()))?;
+    let client_time = client_start.elapsed();
+    let (registration_response, transport_time) = timed_exchange(
+        transport.as_ref(),
+        DriverStep::RegistrationStart,
+        &credential_identifier,
+        start.registration_request,
+    )?;
+    steps.push(step_timing(
+        DriverStep::RegistrationStart,
+        client_time,
+        transport_time,
+    ));
+
+    let client_start = Instant::now();
+    let finish = client_registration_finish(
+        password,
+        start.client_registration,
+        registration_response,
+        config.identity_mode,
+    )
+    .map_err(|e| DriverError::Registration(e.to_string()))?;
+    let client_time = client_start.elapsed();
+    let (_, transport_time) = timed_exchange(
+        transport.as_ref(),
+        DriverStep::RegistrationFinish,
+        &credential_identifier,
+        finish.registration_upload,
+    )?;
+    steps.push(step_timing(
+        DriverStep::RegistrationFinish,
+        client_time,
+        transport_time,
+    ));
+
+    Ok(RegistrationReport {
+        steps,
+        export_key: finish.export_key,
+    })
+}
+
+/// Runs a full login for `credential_identifier` through `transport`.
+#[uniffi::export]
+pub fn run_login(
+    transport: Arc<dyn Transport>,
+    credential_identifier: Vec<u8>,
+    password: Vec<u8>,
+    config: DriverConfig,
+) -> Result<LoginReport, DriverError> {
+    let mut steps = Vec::new();
+
+    let client_start = Instant::now();
+    let start =
+        client_login_start(password.clone()).map_err(|e| DriverError::Login(e.to_string()))?;
+    let client_time = client_start.elapsed();
+    let (credential_response, transport_time) = timed_exchange(
+        transport.as_ref(),
+        DriverStep::LoginStart,
+        &credential_identifier,
+        start.credential_request,
+    )?;
+    steps.push(step_timing(
+        DriverStep::LoginStart,
+        client_time,
+        transport_time,
+    ));
+
+    let client_start = Instant::now();
+    let finish = client_login_finish(
+        credential_response,
+        start.client_registration,
+        password,
+        config.context,
+        config.identity_mode,
+    )
+    .map_err(|e| DriverError::Login(e.to_string()))?;
+    let client_time = client_start.elapsed();
+    let (_, transport_time) = timed_exchange(
+        transport.as_ref(),
+        DriverStep::LoginFinish,
+        &credential_identifier,
+        finish.credential_finalization,
+    )?;
+    steps.push(step_timing(
+        DriverStep::LoginFinish,
+        client_time,
+        transport_time,
+    ));
+
+    Ok(LoginReport {
+        steps,
+        export_key: finish.export_key,
+        session_key_fingerprint: session_key_fingerprint(&finish.session_key),
+    })
+}
+
+/// Returns the fingerprint reported for a session key: the first 16 bytes of its SHA-256, in hex.
+#[uniffi::export]
+pub fn session_key_fingerprint(session_key: &[u8]) -> String {
+    Sha256::digest(session_key)[..16]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn timed_exchange(
+    transport: &dyn Transport,
+    step: DriverStep,
+    credential_identifier: &[u8],
+    message: Vec<u8>,
+) -> Result<(Vec<u8>, Duration), DriverError> {
+    let start = Instant::now();
+    let reply = transport.exchange(step, credential_identifier.to_vec(), message)?;
+    Ok((reply, start.elapsed()))
+}
+
+fn step_timing(step: DriverStep, client_time: Duration, transport_time: Duration) -> StepTiming {
+    StepTiming {
+        step,
+        client_micros: client_time.as_micros() as u64,
+        transport_micros: transport_time.as_micros() as u64,
+    }
+}
+
+/// An in-process stand-in server, for running the driver without a deployment (e.g. in CI).
+pub struct LocalServer {
+    server_setup: Vec<u8>,
+    config: DriverConfig,
+    password_files: Mutex<HashMap<Vec<u8>, Vec<u8>>>,
+    server_logins: Mutex<HashMap<Vec<u8>, Vec<u8>>>,
+    session_keys: Mutex<HashMap<Vec<u8>, Vec<u8>>>,
+}
+
+impl LocalServer {
+    pub fn new(server_setup: Vec<u8>, config: DriverConfig) -> Self {
+        Self {
+            server_setup,
+            config,
+            password_files: Mutex::new(HashMap::new()),
+            server_logins: Mutex::new(HashMap::new()),
+            session_keys: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the session key of the last successful login of `credential_identifier`.
+    pub fn session_key(&self, credential_identifier: &[u8]) -> Option<Vec<u8>> {
+        self.session_keys
+            .lock()
+            .ok()?
+            .get(credential_identifier)
+            .cloned()
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> Result<std::sync::MutexGuard<'_, T>, DriverError> {
+    mutex
+        .lock()
+        .map_err(|e| DriverError::Transport(format!("{:?}", e)))
+}
+
+impl Transport for LocalServer {
+    fn exchange(
+        &self,
+        step: DriverStep,
+        credential_identifier: Vec<u8>,
+        message: Vec<u8>,
+    ) -> Result<Vec<u8>, DriverError> {
+        match step {
+            DriverStep::RegistrationStart => server_registration_start(
+                self.server_setup.clone(),
+                message,
+                &credential_identifier,
+            )
+            .map_err(|e| DriverError::Registration(e.to_string())),
+            DriverStep::RegistrationFinish => {
+                let password_file = server_registration_finish(message)
+                    .map_err(|e| DriverError::Registration(e.to_string()))?;
+                lock(&self.password_files)?.insert(credential_identifier, password_file);
+                Ok(Vec::new())
+            }
+            DriverStep::LoginStart => {
+                let password_file = lock(&self.password_files)?
+                    .get(&credential_identifier)
+                    .cloned()
+                    .unwrap_or_default();
+                let result = server_login_start(
+                    self.server_setup.clone(),
+                    password_file,
+                    message,
+                    &credential_identifier,
+                    Some(self.config.context.clone()),
+                    self.config.identity_mode.clone(),
+                )
+                .map_err(|e| DriverError::Login(e.to_string()))?;
+                lock(&self.server_logins)?.insert(credential_identifier, result.server_login);
+                Ok(result.credential_response)
+            }
+            DriverStep::LoginFinish => {
+                let server_login = lock(&self.server_logins)?
+                    .remove(&credential_identifier)
+                    .ok_or_else(|| DriverError::Login("No login in progress".into()))?;
+                let session_key = server_login_finish(
+                    server_login,
+                    message,
+                    Some(self.config.context.clone()),
+                    self.config.identity_mode.clone(),
+                )
+                .map_err(|e| DriverError::Login(e.to_string()))?;
+                lock(&self.session_keys)?.insert(credential_identifier, session_key);
+                Ok(Vec::new())
+            }
+        }
+    }
+}
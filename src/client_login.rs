@@ -4,9 +4,17 @@
 
 //! This module handles the client-side of the OPAQUE login process.
 
-use crate::common::{DefaultCipherSuite, LoginError};
-use opaque_ke::{ClientLogin, ClientLoginFinishParameters, Identifiers};
+use crate::{
+    common::{DefaultCipherSuite, IdentityMode, LoginError},
+    inspect::{NH, NM, NN, NOE, NPK, NSK},
+};
+use hkdf::Hkdf;
+use opaque_ke::{ksf::Ksf, CipherSuite, ClientLogin, ClientLoginFinishParameters};
 use rand::rngs::OsRng;
+use sha2::Sha256;
+use voprf::Group;
+
+type OprfCs = <DefaultCipherSuite as CipherSuite>::OprfCs;
 
 #[derive(uniffi::Record)]
 pub struct ClientLoginStartResult {
@@ -19,6 +27,10 @@ pub struct ClientLoginFinishResult {
     pub credential_finalization: Vec<u8>,
     pub session_key: Vec<u8>,
     pub export_key: Vec<u8>,
+    /// The client's static public key, as in the password file.
+    pub client_public_key: Vec<u8>,
+    /// The server's static public key, recovered from the envelope.
+    pub server_public_key: Vec<u8>,
 }
 
 /// Initiates the OPAQUE login flow on the client side.
@@ -45,16 +57,14 @@ pub fn client_login_start(password: Vec<u8>) -> Result<ClientLoginStartResult, L
 /// - `client_registration`: The client's internal login state from `client_login_start`.
 /// - `password`: The user's password.
 /// - `context`: Application-specific context for the session.
-/// - `client_identifier`: The client's identity.
-/// - `server_identifier`: The server's identity.
+/// - `identity_mode`: The client and server identities, as used at registration.
 #[uniffi::export]
 pub fn client_login_finish(
     credential_response: Vec<u8>,
     client_registration: Vec<u8>,
     password: Vec<u8>,
     context: Vec<u8>,
-    client_identifier: Vec<u8>,
-    server_identifier: Vec<u8>,
+    identity_mode: IdentityMode,
 ) -> Result<ClientLoginFinishResult, LoginError> {
     let mut rng = OsRng;
 
@@ -67,10 +77,7 @@ pub fn client_login_finish(
 
     let params = ClientLoginFinishParameters {
         context: Some(&context),
-        identifiers: Identifiers {
-            client: Some(&client_identifier),
-            server: Some(&server_identifier),
-        },
+        identifiers: identity_mode.identifiers(),
         ksf: None,  // Use default KSF from CipherSuite
     };
 
@@ -89,9 +96,78 @@ pub fn client_login_finish(
     };
 
 
+    let client_public_key =
+        recover_client_public_key(&client_registration, &credential_response, &password)?;
+
     Ok(ClientLoginFinishResult {
         credential_finalization: finish_res.message.serialize().to_vec(),
         session_key: finish_res.session_key.to_vec(),
         export_key: finish_res.export_key.to_vec(),
+        client_public_key,
+        server_public_key: finish_res.server_s_pk.serialize().to_vec(),
     })
 }
+
+/// Recovers the client's static public key, which opaque-ke does not return from a login,
+/// by repeating the key recovery of RFC 9807, Sections 4.1.3 and 6.4.2.
+///
+/// Only called after `ClientLogin::finish` succeeded, so the state and response are known
+/// to be well-formed.
+fn recover_client_public_key(
+    client_login: &[u8],
+    credential_response: &[u8],
+    password: &[u8],
+) -> Result<Vec<u8>, LoginError> {
+    fn err<E: std::fmt::Debug>(e: E) -> LoginError {
+        LoginError::Generic(format!("{:?}", e))
+    }
+
+    // The login state starts with the OPRF blind, the response with the evaluated element.
+    let oprf_client =
+        voprf::OprfClient::<OprfCs>::deserialize(&client_login[..NSK]).map_err(err)?;
+    let evaluation_element =
+        voprf::EvaluationElement::<OprfCs>::deserialize(&credential_response[..NOE])
+            .map_err(err)?;
+    let oprf_output = oprf_client
+        .finalize(password, &evaluation_element)
+        .map_err(err)?;
+    let stretched_oprf_output = <DefaultCipherSuite as CipherSuite>::Ksf::default()
+        .hash(oprf_output)
+        .map_err(err)?;
+    let (_, randomized_password) =
+        Hkdf::<Sha256>::extract(None, &[oprf_output, stretched_oprf_output].concat());
+
+    let mut masking_key = [0u8; NH];
+    randomized_password
+        .expand(b"MaskingKey", &mut masking_key)
+        .map_err(err)?;
+    let masking_nonce = &credential_response[NOE..NOE + NN];
+    let masked_response = &credential_response[NOE + NN..NOE + NN + NPK + NN + NM];
+    let mut response = [0u8; NPK + NN + NM];
+    Hkdf::<Sha256>::from_prk(&masking_key)
+        .map_err(err)?
+        .expand_multi_info(&[masking_nonce, b"CredentialResponsePad"], &mut response)
+        .map_err(err)?;
+    response
+        .iter_mut()
+        .zip(masked_response)
+        .for_each(|(r, m)| *r ^= m);
+
+    // The unmasked response is the server public key followed by the envelope.
+    let envelope_nonce = &response[NPK..NPK + NN];
+    let mut seed = [0u8; NSK];
+    randomized_password
+        .expand_multi_info(&[envelope_nonce, b"PrivateKey"], &mut seed)
+        .map_err(err)?;
+    let private_key = voprf::derive_key::<OprfCs>(
+        &seed,
+        b"OPAQUE-DeriveDiffieHellmanKeyPair",
+        voprf::Mode::Oprf,
+    )
+    .map_err(err)?;
+
+    Ok(<OprfCs as voprf::CipherSuite>::Group::serialize_elem(
+        <OprfCs as voprf::CipherSuite>::Group::base_elem() * private_key,
+    )
+    .to_vec())
+}
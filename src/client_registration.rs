@@ -4,8 +4,11 @@
 
 //! This module handles the client-side of the OPAQUE registration process.
 
-use crate::common::{DefaultCipherSuite, RegistrationError};
-use opaque_ke::{ClientRegistration, ClientRegistrationFinishParameters, Identifiers, RegistrationResponse};
+use crate::{
+    common::{DefaultCipherSuite, IdentityMode, RegistrationError},
+    inspect::NPK,
+};
+use opaque_ke::{ClientRegistration, ClientRegistrationFinishParameters, RegistrationResponse};
 use rand::rngs::OsRng;
 
 #[derive(uniffi::Record)]
@@ -18,6 +21,8 @@ pub struct ClientRegistrationStartResult {
 pub struct ClientRegistrationFinishResult {
     pub registration_upload: Vec<u8>,
     pub export_key: Vec<u8>,
+    /// The client's static public key, as stored in the password file.
+    pub client_public_key: Vec<u8>,
 }
 
 /// Initiates the OPAQUE registration flow on the client side.
@@ -42,15 +47,14 @@ pub fn client_registration_start(
 /// Completes the OPAQUE registration flow on the client side.
 ///
 /// This function takes the user's password, the client's registration state,
-/// the server's registration response, and optional client and server identifiers.
+/// the server's registration response, and the identity mode, which logins must repeat.
 /// It returns a RegistrationUpload to be sent to the server and the export key.
 #[uniffi::export]
 pub fn client_registration_finish(
     password: Vec<u8>,
     client_registration: Vec<u8>,
     registration_response: Vec<u8>,
-    client_identifier: Option<Vec<u8>>,
-    server_identifier: Option<Vec<u8>>,
+    identity_mode: IdentityMode,
 ) -> Result<ClientRegistrationFinishResult, RegistrationError> {
     let mut rng = OsRng;
 
@@ -67,10 +71,7 @@ pub fn client_registration_finish(
         };
 
     let params = ClientRegistrationFinishParameters {
-        identifiers: Identifiers {
-            client: client_identifier.as_deref(),
-            server: server_identifier.as_deref(),
-        },
+        identifiers: identity_mode.identifiers(),
         ksf: None,
     };
 
@@ -83,8 +84,13 @@ pub fn client_registration_finish(
         )
         .map_err(|e| RegistrationError::Generic(format!("{:?}", e)))?;
 
+    let registration_upload = result.message.serialize().to_vec();
+    // The upload starts with the client public key.
+    let client_public_key = registration_upload[..NPK].to_vec();
+
     Ok(ClientRegistrationFinishResult {
-        registration_upload: result.message.serialize().to_vec(),
+        registration_upload,
         export_key: result.export_key.to_vec(),
+        client_public_key,
     })
 }
@@ -2,7 +2,7 @@
 //
 // SPDX-License-Identifier: EUPL-1.2
 
-use opaque_ke::CipherSuite;
+use opaque_ke::{CipherSuite, Identifiers};
 
 #[derive(Debug, thiserror::Error, uniffi::Error)]
 pub enum RegistrationError {
@@ -16,6 +16,62 @@ pub enum LoginError {
     Generic(String),
 }
 
+#[derive(Debug, thiserror::Error, uniffi::Error)]
+pub enum ServerSetupError {
+    #[error("Server setup failed: {0}")]
+    Generic(String),
+}
+
+/// The client and server identities bound into the OPAQUE envelope and key exchange.
+///
+/// Registration and every later login must use the same mode and identities, or the login
+/// fails.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Enum)]
+pub enum IdentityMode {
+    /// Use the client's and the server's public keys as their identities, the RFC 9807
+    /// default.
+    DefaultPublicKeys,
+    /// Use the given client and server identities, e.g. a user name and a domain.
+    Explicit { client: Vec<u8>, server: Vec<u8> },
+}
+
+impl IdentityMode {
+    pub(crate) fn identifiers(&self) -> Identifiers<'_> {
+        match self {
+            IdentityMode::DefaultPublicKeys => Identifiers {
+                client: None,
+                server: None,
+            },
+            IdentityMode::Explicit { client, server } => Identifiers {
+                client: Some(client),
+                server: Some(server),
+            },
+        }
+    }
+}
+
+/// Identifier of `DefaultCipherSuite`, stored alongside persisted artifacts.
+pub const DEFAULT_CIPHER_SUITE_ID: &str = "P256-SHA256-TripleDH-Identity";
+
+/// Version of the password file format produced by `server_registration_finish`.
+pub const PASSWORD_FILE_VERSION: u32 = 1;
+
+/// Appends `value` to `out` with a 2-byte big-endian length prefix.
+///
+/// Fails with a message naming the value, e.g. "the device ID", if it is longer than 65535
+/// bytes; callers map it into their own error.
+pub(crate) fn push_length_prefixed(
+    out: &mut Vec<u8>,
+    name: &str,
+    value: &[u8],
+) -> Result<(), String> {
+    let len =
+        u16::try_from(value.len()).map_err(|_| format!("{} is longer than 65535 bytes", name))?;
+    out.extend_from_slice(&len.to_be_bytes());
+    out.extend_from_slice(value);
+    Ok(())
+}
+
 #[allow(dead_code)]
 pub struct DefaultCipherSuite;
 
@@ -0,0 +1,268 @@
+// SPDX-FileCopyrightText: 2026 Digg - Agency for Digital Government
+//
+// SPDX-License-Identifier: EUPL-1.2
+
+//! This module derives the `credential_identifier`/`client_id` passed to
+//! `server_registration_start` and `server_login_start` from a user identifier.
+//!
+//! The identifier is first canonicalized by its kind, so e.g. `Alice@Example.com ` and
+//! `alice@example.com` are the same user, and then hashed with HMAC-SHA256 under a pepper
+//! held by the server. The store is keyed by the derived identifier only, so it does not
+//! reveal the users' emails or usernames, and without the pepper they cannot be confirmed
+//! by hashing guesses.
+
+use caseless::Caseless;
+use hmac::{Hmac, Mac};
+use icu_properties::{props::DefaultIgnorableCodePoint, CodePointSetData};
+use rand::{rngs::OsRng, RngCore};
+use sha2::Sha256;
+use unicode_normalization::UnicodeNormalization;
+
+/// Prefix of the HMAC input of every derived credential identifier.
+const CREDENTIAL_IDENTIFIER_TAG: &[u8] = b"OPAQUE-Credential-Identifier-v1";
+const MIN_PEPPER_LEN: usize = 32;
+
+#[derive(Debug, thiserror::Error, uniffi::Error)]
+pub enum CredentialIdentifierError {
+    #[error("Invalid parameter: {0}")]
+    InvalidParameter(String),
+    #[error("Invalid identifier: {0}")]
+    InvalidIdentifier(String),
+}
+
+/// The kind of a user identifier, which decides how it is canonicalized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum IdentifierKind {
+    /// An email address, case-folded as a whole with NFKC_Casefold.
+    Email,
+    /// A username, case-folded with NFKC_Casefold.
+    Username,
+    /// A UUID, in any of its usual notations.
+    Uuid,
+}
+
+impl IdentifierKind {
+    fn label(self) -> &'static [u8] {
+        match self {
+            IdentifierKind::Email => b"email",
+            IdentifierKind::Username => b"username",
+            IdentifierKind::Uuid => b"uuid",
+        }
+    }
+}
+
+/// NFKC_Casefold (Unicode Section 3.13, R4): default-ignorable characters are removed and
+/// the identifier is fully case-folded, not just lowercased, so e.g. `Straße` and
+/// `STRASSE` fold alike. Folding is applied before and after the compatibility
+/// decomposition, as in the compatibility caseless match, and the result composed to NFKC.
+fn fold(identifier: &str) -> String {
+    let default_ignorable = CodePointSetData::new::<DefaultIgnorableCodePoint>();
+    identifier
+        .chars()
+        .filter(|c| !default_ignorable.contains(*c))
+        .nfd()
+        .default_case_fold()
+        .nfkd()
+        .default_case_fold()
+        .nfkc()
+        .collect()
+}
+
+fn canonicalize_uuid(identifier: &str) -> Result<String, CredentialIdentifierError> {
+    let lowercase = identifier.to_ascii_lowercase();
+    let bare = lowercase
+        .strip_prefix("urn:uuid:")
+        .or_else(|| lowercase.strip_prefix('{')?.strip_suffix('}'))
+        .unwrap_or(&lowercase);
+    let digits: String = bare.chars().filter(|c| *c != '-').collect();
+    if digits.len() != 32 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(CredentialIdentifierError::InvalidIdentifier(
+            "not a UUID".into(),
+        ));
+    }
+    Ok(format!(
+        "{}-{}-{}-{}-{}",
+        &digits[..8],
+        &digits[8..12],
+        &digits[12..16],
+        &digits[16..20],
+        &digits[20..]
+    ))
+}
+
+/// Returns the canonical form of a user identifier.
+///
+/// Surrounding whitespace is trimmed for every kind. Emails and usernames are normalized
+/// with NFKC_Casefold, must not be empty or contain control characters, and emails
+/// must have exactly one `@` with text on both sides. UUIDs are accepted with or without
+/// hyphens, in braces or as a `urn:uuid:` URN, and returned lowercase and hyphenated.
+#[uniffi::export]
+pub fn credential_identifier_canonicalize(
+    identifier: String,
+    kind: IdentifierKind,
+) -> Result<String, CredentialIdentifierError> {
+    let trimmed = identifier.trim();
+    if kind == IdentifierKind::Uuid {
+        return canonicalize_uuid(trimmed);
+    }
+
+    let canonical = fold(trimmed);
+    if canonical.is_empty() {
+        return Err(CredentialIdentifierError::InvalidIdentifier(
+            "the identifier is empty".into(),
+        ));
+    }
+    if canonical.chars().any(char::is_control) {
+        return Err(CredentialIdentifierError::InvalidIdentifier(
+            "the identifier contains control characters".into(),
+        ));
+    }
+    if kind == IdentifierKind::Email {
+        match canonical.split_once('@') {
+            Some((local, domain))
+                if !local.is_empty() && !domain.is_empty() && !domain.contains('@') => {}
+            _ => {
+                return Err(CredentialIdentifierError::InvalidIdentifier(
+                    "not an email address".into(),
+                ))
+            }
+        }
+    }
+    Ok(canonical)
+}
+
+/// Derives the 32-byte credential identifier of a user identifier under `pepper`.
+///
+/// The result is HMAC-SHA256, keyed with the pepper, of a format tag, the identifier kind
+/// and the canonical identifier, each but the tag with a 2-byte big-endian length prefix.
+/// The pepper must be at least 32 bytes and stay the same for the lifetime of the store.
+#[uniffi::export]
+pub fn credential_identifier_derive(
+    pepper: Vec<u8>,
+    identifier: String,
+    kind: IdentifierKind,
+) -> Result<Vec<u8>, CredentialIdentifierError> {
+    if pepper.len() < MIN_PEPPER_LEN {
+        return Err(CredentialIdentifierError::InvalidParameter(format!(
+            "the pepper must be at least {} bytes",
+            MIN_PEPPER_LEN
+        )));
+    }
+    let canonical = credential_identifier_canonicalize(identifier, kind)?;
+    let len = u16::try_from(canonical.len()).map_err(|_| {
+        CredentialIdentifierError::InvalidIdentifier(
+            "the identifier is longer than 65535 bytes".into(),
+        )
+    })?;
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(&pepper)
+        .map_err(|e| CredentialIdentifierError::InvalidParameter(format!("{:?}", e)))?;
+    mac.update(CREDENTIAL_IDENTIFIER_TAG);
+    mac.update(&(kind.label().len() as u16).to_be_bytes());
+    mac.update(kind.label());
+    mac.update(&len.to_be_bytes());
+    mac.update(canonical.as_bytes());
+    Ok(mac.finalize().into_bytes().to_vec())
+}
+
+/// Generates a random 32-byte pepper for [`credential_identifier_derive`].
+#[uniffi::export]
+pub fn credential_identifier_generate_pepper() -> Vec<u8> {
+    let mut pepper = vec![0u8; MIN_PEPPER_LEN];
+    OsRng.fill_bytes(&mut pepper);
+    pepper
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn canonical(identifier: &str, kind: IdentifierKind) -> String {
+        credential_identifier_canonicalize(identifier.into(), kind).unwrap()
+    }
+
+    #[test]
+    fn test_canonicalization() {
+        assert_eq!(
+            canonical("  Alice@Example.COM\n", IdentifierKind::Email),
+            "alice@example.com"
+        );
+        assert_eq!(
+            canonical("\u{c5}SA", IdentifierKind::Username),
+            canonical("a\u{30a}sa", IdentifierKind::Username)
+        );
+        // Full-width letters fold to ASCII
+        assert_eq!(canonical("\u{ff22}ob", IdentifierKind::Username), "bob");
+        // Full case folding, not just lowercasing
+        assert_eq!(
+            canonical("Stra\u{df}e", IdentifierKind::Username),
+            canonical("STRASSE", IdentifierKind::Username)
+        );
+        // Final and non-final sigma fold alike
+        assert_eq!(
+            canonical("\u{3bf}\u{3b4}\u{3bf}\u{3c2}", IdentifierKind::Username),
+            canonical("\u{39f}\u{394}\u{39f}\u{3a3}", IdentifierKind::Username)
+        );
+        // Default-ignorable characters are removed
+        assert_eq!(
+            canonical("bo\u{200b}b\u{ad}", IdentifierKind::Username),
+            "bob"
+        );
+
+        let uuid = "123e4567-e89b-12d3-a456-426614174000";
+        for notation in [
+            "123E4567-E89B-12D3-A456-426614174000",
+            "123e4567e89b12d3a456426614174000",
+            "{123e4567-e89b-12d3-a456-426614174000}",
+            "urn:uuid:123e4567-e89b-12d3-a456-426614174000",
+        ] {
+            assert_eq!(canonical(notation, IdentifierKind::Uuid), uuid);
+        }
+    }
+
+    #[test]
+    fn test_invalid_identifiers_are_rejected() {
+        for (identifier, kind) in [
+            ("  ", IdentifierKind::Username),
+            ("bob\u{0}", IdentifierKind::Username),
+            ("alice", IdentifierKind::Email),
+            ("@example.com", IdentifierKind::Email),
+            ("alice@", IdentifierKind::Email),
+            ("a@b@example.com", IdentifierKind::Email),
+            ("123e4567-e89b-12d3-a456", IdentifierKind::Uuid),
+            ("123e4567-e89b-12d3-a456-42661417400g", IdentifierKind::Uuid),
+        ] {
+            assert!(matches!(
+                credential_identifier_canonicalize(identifier.into(), kind),
+                Err(CredentialIdentifierError::InvalidIdentifier(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_derivation() {
+        let pepper = credential_identifier_generate_pepper();
+        let derive = |pepper: &[u8], identifier: &str, kind: IdentifierKind| {
+            credential_identifier_derive(pepper.to_vec(), identifier.into(), kind).unwrap()
+        };
+
+        let id = derive(&pepper, "alice@example.com", IdentifierKind::Email);
+        assert_eq!(id.len(), 32);
+        assert_eq!(
+            derive(&pepper, " Alice@Example.com", IdentifierKind::Email),
+            id
+        );
+        assert_ne!(
+            derive(&pepper, "alice@example.com", IdentifierKind::Username),
+            id
+        );
+        assert_ne!(
+            derive(&[7; 32], "alice@example.com", IdentifierKind::Email),
+            id
+        );
+        assert!(matches!(
+            credential_identifier_derive(vec![0; 16], "alice".into(), IdentifierKind::Username),
+            Err(CredentialIdentifierError::InvalidParameter(_))
+        ));
+    }
+}
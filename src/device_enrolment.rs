@@ -0,0 +1,302 @@
+// SPDX-FileCopyrightText: 2026 Digg - Agency for Digital Government
+//
+// SPDX-License-Identifier: EUPL-1.2
+
+//! This module lets several devices of one account share an account key while each keeps
+//! its own device key.
+//!
+//! The first device creates a random account secret and wraps it under the export key of
+//! its registration; the server stores the wrapped secret next to the password file and
+//! the account public key. A new device logs in, gets the wrapped secret back from the
+//! server and unwraps it with the export key from `client_login_finish`. The account key
+//! then signs the new device's public key, and the server accepts the device if the
+//! signature verifies against the account public key. The server never sees the account
+//! secret, since it never learns the export key.
+//!
+//! The account key is the Ed25519 key derived from the account secret as by
+//! `signing_public_key`, so it survives password changes: after one, the client rewraps
+//! the same secret under the new export key with [`enrolment_wrap_account_secret`].
+
+use crate::{
+    common::push_length_prefixed,
+    signing::{signing_public_key, signing_sign, signing_verify, SigningAlgorithm},
+};
+use chacha20poly1305::{
+    aead::{Aead, AeadCore, KeyInit, Payload},
+    XChaCha20Poly1305, XNonce,
+};
+use hkdf::Hkdf;
+use rand::{rngs::OsRng, RngCore};
+use sha2::Sha256;
+
+/// HKDF info of the key the account secret is wrapped with.
+const WRAP_KEY_INFO: &[u8] = b"OPAQUE-ExportKey-AccountSecretWrap";
+/// Associated data of the wrapped account secret, identifying its format.
+const WRAPPED_SECRET_TAG: &[u8] = b"OPAQUE-Wrapped-Account-Secret-v1";
+/// Key info of the account key derived from the account secret.
+const ACCOUNT_KEY_INFO: &[u8] = b"OPAQUE-Account-Key";
+/// Prefix of every signed device enrolment message.
+const DEVICE_ENROLMENT_TAG: &[u8] = b"OPAQUE-Device-Enrolment-v1";
+
+const ACCOUNT_SECRET_LEN: usize = 32;
+const NONCE_LEN: usize = 24;
+const TAG_LEN: usize = 16;
+
+#[derive(Debug, thiserror::Error, uniffi::Error)]
+pub enum DeviceEnrolmentError {
+    #[error("Device enrolment failed: {0}")]
+    Generic(String),
+    #[error("Invalid parameter: {0}")]
+    InvalidParameter(String),
+    #[error("The wrapped account secret does not open with this export key")]
+    UnwrapFailed,
+}
+
+#[derive(Clone, uniffi::Record)]
+pub struct AccountEnrolment {
+    /// The account secret, kept on the device.
+    pub account_secret: Vec<u8>,
+    /// The account secret wrapped under the export key, stored by the server.
+    pub wrapped_account_secret: Vec<u8>,
+    /// The public key of the account key, stored by the server to verify device enrolments.
+    pub account_public_key: Vec<u8>,
+}
+
+fn wrap_cipher(export_key: &[u8]) -> Result<XChaCha20Poly1305, DeviceEnrolmentError> {
+    let hkdf = Hkdf::<Sha256>::from_prk(export_key).map_err(|_| {
+        DeviceEnrolmentError::InvalidParameter("the export key must be at least 32 bytes".into())
+    })?;
+    let mut key = [0u8; 32];
+    hkdf.expand(WRAP_KEY_INFO, &mut key)
+        .map_err(|e| DeviceEnrolmentError::Generic(format!("{:?}", e)))?;
+    Ok(XChaCha20Poly1305::new(&key.into()))
+}
+
+fn check_account_secret(account_secret: &[u8]) -> Result<(), DeviceEnrolmentError> {
+    if account_secret.len() != ACCOUNT_SECRET_LEN {
+        return Err(DeviceEnrolmentError::InvalidParameter(format!(
+            "the account secret must be {} bytes",
+            ACCOUNT_SECRET_LEN
+        )));
+    }
+    Ok(())
+}
+
+/// The message the account key signs to enrol a device.
+fn device_enrolment_message(
+    device_id: &str,
+    device_public_key: &[u8],
+) -> Result<Vec<u8>, DeviceEnrolmentError> {
+    let mut message = DEVICE_ENROLMENT_TAG.to_vec();
+    push_length_prefixed(&mut message, "the device ID", device_id.as_bytes())
+        .map_err(DeviceEnrolmentError::InvalidParameter)?;
+    push_length_prefixed(&mut message, "the device public key", device_public_key)
+        .map_err(DeviceEnrolmentError::InvalidParameter)?;
+    Ok(message)
+}
+
+/// Creates a random account secret on the first device, with its wrapping under
+/// `export_key` (from `client_registration_finish`) and its account public key.
+#[uniffi::export]
+pub fn enrolment_create_account(
+    export_key: Vec<u8>,
+) -> Result<AccountEnrolment, DeviceEnrolmentError> {
+    let mut account_secret = vec![0u8; ACCOUNT_SECRET_LEN];
+    OsRng.fill_bytes(&mut account_secret);
+
+    Ok(AccountEnrolment {
+        wrapped_account_secret: enrolment_wrap_account_secret(export_key, account_secret.clone())?,
+        account_public_key: enrolment_account_public_key(account_secret.clone())?,
+        account_secret,
+    })
+}
+
+/// Wraps an account secret under an export key, e.g. the new one after a password change.
+///
+/// The wrapped secret is a random 24-byte nonce followed by the XChaCha20-Poly1305
+/// encryption of the secret, under a key derived from the export key with HKDF-SHA256.
+#[uniffi::export]
+pub fn enrolment_wrap_account_secret(
+    export_key: Vec<u8>,
+    account_secret: Vec<u8>,
+) -> Result<Vec<u8>, DeviceEnrolmentError> {
+    check_account_secret(&account_secret)?;
+    let cipher = wrap_cipher(&export_key)?;
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: &account_secret,
+                aad: WRAPPED_SECRET_TAG,
+            },
+        )
+        .map_err(|e| DeviceEnrolmentError::Generic(format!("{:?}", e)))?;
+
+    let mut wrapped = nonce.to_vec();
+    wrapped.extend_from_slice(&ciphertext);
+    Ok(wrapped)
+}
+
+/// Unwraps an account secret with the export key from `client_login_finish`.
+///
+/// Fails with `UnwrapFailed` if the wrapped secret was made with another export key, e.g.
+/// after a login with another password file, or was modified.
+#[uniffi::export]
+pub fn enrolment_unwrap_account_secret(
+    export_key: Vec<u8>,
+    wrapped_account_secret: Vec<u8>,
+) -> Result<Vec<u8>, DeviceEnrolmentError> {
+    if wrapped_account_secret.len() != NONCE_LEN + ACCOUNT_SECRET_LEN + TAG_LEN {
+        return Err(DeviceEnrolmentError::InvalidParameter(format!(
+            "the wrapped account secret must be {} bytes",
+            NONCE_LEN + ACCOUNT_SECRET_LEN + TAG_LEN
+        )));
+    }
+    let cipher = wrap_cipher(&export_key)?;
+    let (nonce, ciphertext) = wrapped_account_secret.split_at(NONCE_LEN);
+    cipher
+        .decrypt(
+            XNonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: WRAPPED_SECRET_TAG,
+            },
+        )
+        .map_err(|_| DeviceEnrolmentError::UnwrapFailed)
+}
+
+/// Returns the 32-byte Ed25519 public key of the account key of an account secret.
+#[uniffi::export]
+pub fn enrolment_account_public_key(
+    account_secret: Vec<u8>,
+) -> Result<Vec<u8>, DeviceEnrolmentError> {
+    check_account_secret(&account_secret)?;
+    signing_public_key(
+        SigningAlgorithm::Ed25519,
+        account_secret,
+        ACCOUNT_KEY_INFO.to_vec(),
+    )
+    .map_err(|e| DeviceEnrolmentError::Generic(format!("{:?}", e)))
+}
+
+/// Signs the enrolment of a device key with the account key.
+///
+/// `device_public_key` is the device's own public key, in whatever encoding the
+/// application uses; the signature covers it together with `device_id`.
+#[uniffi::export]
+pub fn enrolment_sign_device(
+    account_secret: Vec<u8>,
+    device_id: String,
+    device_public_key: Vec<u8>,
+) -> Result<Vec<u8>, DeviceEnrolmentError> {
+    check_account_secret(&account_secret)?;
+    let message = device_enrolment_message(&device_id, &device_public_key)?;
+    signing_sign(
+        SigningAlgorithm::Ed25519,
+        account_secret,
+        ACCOUNT_KEY_INFO.to_vec(),
+        message,
+    )
+    .map_err(|e| DeviceEnrolmentError::Generic(format!("{:?}", e)))
+}
+
+/// Verifies a device enrolment from [`enrolment_sign_device`] against the account public
+/// key, returning whether the server should accept the device key.
+#[uniffi::export]
+pub fn enrolment_verify_device(
+    account_public_key: Vec<u8>,
+    device_id: String,
+    device_public_key: Vec<u8>,
+    signature: Vec<u8>,
+) -> Result<bool, DeviceEnrolmentError> {
+    let message = device_enrolment_message(&device_id, &device_public_key)?;
+    signing_verify(
+        SigningAlgorithm::Ed25519,
+        account_public_key,
+        message,
+        signature,
+    )
+    .map_err(|e| DeviceEnrolmentError::InvalidParameter(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap_and_unwrap() {
+        let account = enrolment_create_account(vec![1; 64]).unwrap();
+        assert_eq!(account.wrapped_account_secret.len(), 72);
+        assert_eq!(
+            enrolment_unwrap_account_secret(vec![1; 64], account.wrapped_account_secret.clone())
+                .unwrap(),
+            account.account_secret
+        );
+        assert_eq!(
+            enrolment_account_public_key(account.account_secret.clone()).unwrap(),
+            account.account_public_key
+        );
+
+        // Wrapping is randomized, but every wrapping opens to the same secret
+        let rewrapped =
+            enrolment_wrap_account_secret(vec![1; 64], account.account_secret.clone()).unwrap();
+        assert_ne!(rewrapped, account.wrapped_account_secret);
+        assert_eq!(
+            enrolment_unwrap_account_secret(vec![1; 64], rewrapped).unwrap(),
+            account.account_secret
+        );
+    }
+
+    #[test]
+    fn test_unwrap_fails_with_wrong_key_or_modified_secret() {
+        let account = enrolment_create_account(vec![1; 64]).unwrap();
+        assert!(matches!(
+            enrolment_unwrap_account_secret(vec![2; 64], account.wrapped_account_secret.clone()),
+            Err(DeviceEnrolmentError::UnwrapFailed)
+        ));
+
+        let mut modified = account.wrapped_account_secret.clone();
+        modified[30] ^= 1;
+        assert!(matches!(
+            enrolment_unwrap_account_secret(vec![1; 64], modified),
+            Err(DeviceEnrolmentError::UnwrapFailed)
+        ));
+        assert!(matches!(
+            enrolment_unwrap_account_secret(
+                vec![1; 64],
+                account.wrapped_account_secret[1..].to_vec()
+            ),
+            Err(DeviceEnrolmentError::InvalidParameter(_))
+        ));
+        assert!(matches!(
+            enrolment_wrap_account_secret(vec![1; 64], vec![0; 16]),
+            Err(DeviceEnrolmentError::InvalidParameter(_))
+        ));
+    }
+
+    #[test]
+    fn test_device_enrolment_binds_device_id_and_key() {
+        let account = enrolment_create_account(vec![1; 64]).unwrap();
+        let signature = enrolment_sign_device(
+            account.account_secret.clone(),
+            "tablet".into(),
+            b"device key".to_vec(),
+        )
+        .unwrap();
+        let verify = |device_id: &str, device_public_key: &[u8]| {
+            enrolment_verify_device(
+                account.account_public_key.clone(),
+                device_id.into(),
+                device_public_key.to_vec(),
+                signature.clone(),
+            )
+            .unwrap()
+        };
+
+        assert!(verify("tablet", b"device key"));
+        assert!(!verify("phone", b"device key"));
+        assert!(!verify("tablet", b"other key"));
+        assert!(!verify("tablet", b""));
+    }
+}
@@ -0,0 +1,527 @@
+// SPDX-FileCopyrightText: 2026 Digg - Agency for Digital Government
+//
+// SPDX-License-Identifier: EUPL-1.2
+
+//! This module frames the messages of a registration or login flow for transport,
+//! tagging each with a flow ID and step number, and tracks on both sides that the
+//! messages of a flow arrive in order.
+//!
+//! A frame is encoded as:
+//! `version (1) || flow_id (16) || step (1) || type (1) || payload length (4, big endian) || payload`,
+//! where type is 0 for a protocol message and 1 for an error, whose payload is a UTF-8 message.
+
+use rand::{rngs::OsRng, RngCore};
+use std::sync::{Arc, Mutex};
+
+const FRAME_VERSION: u8 = 1;
+const FLOW_ID_LEN: usize = 16;
+const HEADER_LEN: usize = 1 + FLOW_ID_LEN + 1 + 1 + 4;
+
+const FRAME_TYPE_MESSAGE: u8 = 0;
+const FRAME_TYPE_ERROR: u8 = 1;
+
+#[derive(Debug, thiserror::Error, uniffi::Error)]
+pub enum FramingError {
+    #[error("Malformed frame: {0}")]
+    Malformed(String),
+    #[error("Frame belongs to another flow")]
+    FlowMismatch,
+    #[error("Expected {expected:?}, received {received:?}")]
+    OutOfOrder {
+        expected: FlowStep,
+        received: FlowStep,
+    },
+    #[error("It is not this side's turn to send {step:?}")]
+    NotOurTurn { step: FlowStep },
+    #[error("Peer reported an error: {message}")]
+    Remote { message: String },
+    #[error("Flow is already finished")]
+    Finished,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum FlowKind {
+    Registration,
+    Login,
+}
+
+/// The messages of a flow, in the order they are sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum FlowStep {
+    RegistrationRequest,
+    RegistrationResponse,
+    RegistrationUpload,
+    /// KE1
+    CredentialRequest,
+    /// KE2
+    CredentialResponse,
+    /// KE3
+    CredentialFinalization,
+}
+
+impl FlowKind {
+    fn steps(self) -> [FlowStep; 3] {
+        match self {
+            FlowKind::Registration => [
+                FlowStep::RegistrationRequest,
+                FlowStep::RegistrationResponse,
+                FlowStep::RegistrationUpload,
+            ],
+            FlowKind::Login => [
+                FlowStep::CredentialRequest,
+                FlowStep::CredentialResponse,
+                FlowStep::CredentialFinalization,
+            ],
+        }
+    }
+}
+
+impl FlowStep {
+    fn to_byte(self) -> u8 {
+        match self {
+            FlowStep::RegistrationRequest => 1,
+            FlowStep::RegistrationResponse => 2,
+            FlowStep::RegistrationUpload => 3,
+            FlowStep::CredentialRequest => 4,
+            FlowStep::CredentialResponse => 5,
+            FlowStep::CredentialFinalization => 6,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Result<Self, FramingError> {
+        match byte {
+            1 => Ok(FlowStep::RegistrationRequest),
+            2 => Ok(FlowStep::RegistrationResponse),
+            3 => Ok(FlowStep::RegistrationUpload),
+            4 => Ok(FlowStep::CredentialRequest),
+            5 => Ok(FlowStep::CredentialResponse),
+            6 => Ok(FlowStep::CredentialFinalization),
+            _ => Err(FramingError::Malformed(format!("unknown step {}", byte))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct Frame {
+    pub flow_id: Vec<u8>,
+    pub step: FlowStep,
+    /// Set for error frames, in which case `payload` is empty.
+    pub error: Option<String>,
+    pub payload: Vec<u8>,
+}
+
+/// Encodes a frame for transport.
+#[uniffi::export]
+pub fn encode_frame(frame: Frame) -> Result<Vec<u8>, FramingError> {
+    if frame.flow_id.len() != FLOW_ID_LEN {
+        return Err(FramingError::Malformed(format!(
+            "flow ID must be {} bytes",
+            FLOW_ID_LEN
+        )));
+    }
+
+    let (frame_type, payload) = match frame.error {
+        Some(message) => (FRAME_TYPE_ERROR, message.into_bytes()),
+        None => (FRAME_TYPE_MESSAGE, frame.payload),
+    };
+
+    let mut bytes = Vec::with_capacity(HEADER_LEN + payload.len());
+    bytes.push(FRAME_VERSION);
+    bytes.extend_from_slice(&frame.flow_id);
+    bytes.push(frame.step.to_byte());
+    bytes.push(frame_type);
+    bytes.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+    bytes.extend_from_slice(&payload);
+
+    Ok(bytes)
+}
+
+/// Decodes a frame received from the transport.
+#[uniffi::export]
+pub fn decode_frame(bytes: &[u8]) -> Result<Frame, FramingError> {
+    if bytes.len() < HEADER_LEN {
+        return Err(FramingError::Malformed("frame is too short".into()));
+    }
+    if bytes[0] != FRAME_VERSION {
+        return Err(FramingError::Malformed(format!(
+            "unsupported version {}",
+            bytes[0]
+        )));
+    }
+
+    let flow_id = bytes[1..1 + FLOW_ID_LEN].to_vec();
+    let step = FlowStep::from_byte(bytes[1 + FLOW_ID_LEN])?;
+    let frame_type = bytes[2 + FLOW_ID_LEN];
+    let length_bytes: [u8; 4] = bytes[3 + FLOW_ID_LEN..HEADER_LEN]
+        .try_into()
+        .expect("slice has length 4");
+    let payload = &bytes[HEADER_LEN..];
+    if payload.len() != u32::from_be_bytes(length_bytes) as usize {
+        return Err(FramingError::Malformed(
+            "payload length does not match header".into(),
+        ));
+    }
+
+    match frame_type {
+        FRAME_TYPE_MESSAGE => Ok(Frame {
+            flow_id,
+            step,
+            error: None,
+            payload: payload.to_vec(),
+        }),
+        FRAME_TYPE_ERROR => Ok(Frame {
+            flow_id,
+            step,
+            error: Some(String::from_utf8_lossy(payload).into_owned()),
+            payload: Vec::new(),
+        }),
+        _ => Err(FramingError::Malformed(format!(
+            "unknown frame type {}",
+            frame_type
+        ))),
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Role {
+    Client,
+    Server,
+}
+
+/// The state shared by both sides: which step comes next and whether the flow has ended.
+struct FlowMachine {
+    kind: FlowKind,
+    role: Role,
+    flow_id: Option<Vec<u8>>,
+    next: usize,
+    failed: bool,
+}
+
+impl FlowMachine {
+    fn new(kind: FlowKind, role: Role, flow_id: Option<Vec<u8>>) -> Self {
+        Self {
+            kind,
+            role,
+            flow_id,
+            next: 0,
+            failed: false,
+        }
+    }
+
+    /// The client sends the first and last message of a flow, the server the middle one.
+    fn sender_of(index: usize) -> Role {
+        match index % 2 {
+            0 => Role::Client,
+            _ => Role::Server,
+        }
+    }
+
+    fn next_step(&self) -> Result<FlowStep, FramingError> {
+        match self.kind.steps().get(self.next) {
+            Some(step) if !self.failed => Ok(*step),
+            _ => Err(FramingError::Finished),
+        }
+    }
+
+    fn send(&mut self, payload: Vec<u8>) -> Result<Vec<u8>, FramingError> {
+        let step = self.next_step()?;
+        if Self::sender_of(self.next) != self.role {
+            return Err(FramingError::NotOurTurn { step });
+        }
+        let flow_id = self
+            .flow_id
+            .clone()
+            .ok_or(FramingError::NotOurTurn { step })?;
+
+        let frame = encode_frame(Frame {
+            flow_id,
+            step,
+            error: None,
+            payload,
+        })?;
+        self.next += 1;
+
+        Ok(frame)
+    }
+
+    fn send_error(&mut self, message: String) -> Result<Vec<u8>, FramingError> {
+        let step = self.next_step()?;
+        let flow_id = self.flow_id.clone().ok_or(FramingError::Finished)?;
+        self.failed = true;
+
+        encode_frame(Frame {
+            flow_id,
+            step,
+            error: Some(message),
+            payload: Vec::new(),
+        })
+    }
+
+    fn receive(&mut self, bytes: &[u8]) -> Result<Vec<u8>, FramingError> {
+        let frame = decode_frame(bytes)?;
+        let expected = self.next_step()?;
+
+        if matches!(&self.flow_id, Some(flow_id) if *flow_id != frame.flow_id) {
+            return Err(FramingError::FlowMismatch);
+        }
+
+        if let Some(message) = frame.error {
+            self.failed = true;
+            return Err(FramingError::Remote { message });
+        }
+
+        if frame.step != expected || Self::sender_of(self.next) == self.role {
+            return Err(FramingError::OutOfOrder {
+                expected,
+                received: frame.step,
+            });
+        }
+        self.flow_id = Some(frame.flow_id);
+        self.next += 1;
+
+        Ok(frame.payload)
+    }
+
+    fn is_complete(&self) -> bool {
+        !self.failed && self.next == self.kind.steps().len()
+    }
+}
+
+fn lock(machine: &Mutex<FlowMachine>) -> std::sync::MutexGuard<'_, FlowMachine> {
+    // The machine is only mutated after all checks pass, so a poisoned state is still consistent.
+    machine.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// The client side of a framed flow. It starts the flow under a new random flow ID.
+#[derive(uniffi::Object)]
+pub struct ClientFlow {
+    machine: Mutex<FlowMachine>,
+}
+
+#[uniffi::export]
+impl ClientFlow {
+    #[uniffi::constructor]
+    pub fn new(kind: FlowKind) -> Arc<Self> {
+        let mut flow_id = vec![0u8; FLOW_ID_LEN];
+        OsRng.fill_bytes(&mut flow_id);
+
+        Arc::new(Self {
+            machine: Mutex::new(FlowMachine::new(kind, Role::Client, Some(flow_id))),
+        })
+    }
+
+    pub fn flow_id(&self) -> Vec<u8> {
+        lock(&self.machine).flow_id.clone().unwrap_or_default()
+    }
+
+    /// Frames the client's next message, e.g. the output of `client_login_start`.
+    pub fn send(&self, payload: Vec<u8>) -> Result<Vec<u8>, FramingError> {
+        lock(&self.machine).send(payload)
+    }
+
+    /// Checks that a received frame is the server's next message, and returns its payload.
+    pub fn receive(&self, frame: Vec<u8>) -> Result<Vec<u8>, FramingError> {
+        lock(&self.machine).receive(&frame)
+    }
+
+    /// Aborts the flow, returning an error frame to tell the server.
+    pub fn send_error(&self, message: String) -> Result<Vec<u8>, FramingError> {
+        lock(&self.machine).send_error(message)
+    }
+
+    pub fn is_complete(&self) -> bool {
+        lock(&self.machine).is_complete()
+    }
+}
+
+/// The server side of a framed flow. It adopts the flow ID of the first frame it receives.
+#[derive(uniffi::Object)]
+pub struct ServerFlow {
+    machine: Mutex<FlowMachine>,
+}
+
+#[uniffi::export]
+impl ServerFlow {
+    #[uniffi::constructor]
+    pub fn new(kind: FlowKind) -> Arc<Self> {
+        Arc::new(Self {
+            machine: Mutex::new(FlowMachine::new(kind, Role::Server, None)),
+        })
+    }
+
+    /// The flow ID, empty until the first frame has been received.
+    pub fn flow_id(&self) -> Vec<u8> {
+        lock(&self.machine).flow_id.clone().unwrap_or_default()
+    }
+
+    /// Frames the server's next message, e.g. the credential response of `server_login_start`.
+    pub fn send(&self, payload: Vec<u8>) -> Result<Vec<u8>, FramingError> {
+        lock(&self.machine).send(payload)
+    }
+
+    /// Checks that a received frame is the client's next message, and returns its payload.
+    pub fn receive(&self, frame: Vec<u8>) -> Result<Vec<u8>, FramingError> {
+        lock(&self.machine).receive(&frame)
+    }
+
+    /// Aborts the flow, returning an error frame to tell the client.
+    pub fn send_error(&self, message: String) -> Result<Vec<u8>, FramingError> {
+        lock(&self.machine).send_error(message)
+    }
+
+    pub fn is_complete(&self) -> bool {
+        lock(&self.machine).is_complete()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        client_login::{client_login_finish, client_login_start},
+        client_registration::{client_registration_finish, client_registration_start},
+        common::IdentityMode,
+        server::server_setup,
+        server_login::{server_login_finish, server_login_start},
+        server_registration::{server_registration_finish, server_registration_start},
+    };
+
+    #[test]
+    fn framed_registration_and_login_is_ok() {
+        let password = b"password".to_vec();
+        let id = b"client".to_vec();
+        let identity_mode = IdentityMode::Explicit {
+            client: id.clone(),
+            server: id.clone(),
+        };
+        let setup = server_setup();
+
+        let client = ClientFlow::new(FlowKind::Registration);
+        let server = ServerFlow::new(FlowKind::Registration);
+        let reg_start = client_registration_start(password.clone()).unwrap();
+        let frame = client.send(reg_start.registration_request).unwrap();
+        let request = server.receive(frame).unwrap();
+        assert_eq!(server.flow_id(), client.flow_id());
+        let response = server_registration_start(setup.clone(), request, &id).unwrap();
+        let frame = server.send(response).unwrap();
+        let reg_finish = client_registration_finish(
+            password.clone(),
+            reg_start.client_registration,
+            client.receive(frame).unwrap(),
+            identity_mode.clone(),
+        )
+        .unwrap();
+        let frame = client.send(reg_finish.registration_upload).unwrap();
+        let password_file = server_registration_finish(server.receive(frame).unwrap()).unwrap();
+        assert!(client.is_complete() && server.is_complete());
+
+        let client = ClientFlow::new(FlowKind::Login);
+        let server = ServerFlow::new(FlowKind::Login);
+        let login_start = client_login_start(password.clone()).unwrap();
+        let frame = client.send(login_start.credential_request).unwrap();
+        let server_start = server_login_start(
+            setup,
+            password_file,
+            server.receive(frame).unwrap(),
+            &id,
+            None,
+            identity_mode.clone(),
+        )
+        .unwrap();
+        let frame = server.send(server_start.credential_response).unwrap();
+        let login_finish = client_login_finish(
+            client.receive(frame).unwrap(),
+            login_start.client_registration,
+            password,
+            Vec::new(),
+            identity_mode.clone(),
+        )
+        .unwrap();
+        let frame = client.send(login_finish.credential_finalization).unwrap();
+        let session_key = server_login_finish(
+            server_start.server_login,
+            server.receive(frame).unwrap(),
+            None,
+            identity_mode,
+        )
+        .unwrap();
+        assert_eq!(session_key, login_finish.session_key);
+        assert!(client.is_complete() && server.is_complete());
+        assert!(matches!(
+            client.send(Vec::new()),
+            Err(FramingError::Finished)
+        ));
+    }
+
+    #[test]
+    fn ke3_before_ke2_is_rejected() {
+        let client = ClientFlow::new(FlowKind::Login);
+        let server = ServerFlow::new(FlowKind::Login);
+        server
+            .receive(client.send(b"ke1".to_vec()).unwrap())
+            .unwrap();
+
+        let ke3 = encode_frame(Frame {
+            flow_id: client.flow_id(),
+            step: FlowStep::CredentialFinalization,
+            error: None,
+            payload: b"ke3".to_vec(),
+        })
+        .unwrap();
+        assert!(matches!(
+            server.receive(ke3),
+            Err(FramingError::OutOfOrder {
+                expected: FlowStep::CredentialResponse,
+                received: FlowStep::CredentialFinalization,
+            })
+        ));
+        assert!(matches!(
+            client.send(b"ke3".to_vec()),
+            Err(FramingError::NotOurTurn {
+                step: FlowStep::CredentialResponse
+            })
+        ));
+    }
+
+    #[test]
+    fn foreign_flow_and_error_frames_are_rejected() {
+        let client = ClientFlow::new(FlowKind::Login);
+        let server = ServerFlow::new(FlowKind::Login);
+        server
+            .receive(client.send(b"ke1".to_vec()).unwrap())
+            .unwrap();
+
+        let other = ClientFlow::new(FlowKind::Login);
+        assert!(matches!(
+            server.receive(other.send(b"ke1".to_vec()).unwrap()),
+            Err(FramingError::FlowMismatch)
+        ));
+
+        let error = server.send_error("unknown user".into()).unwrap();
+        assert!(matches!(
+            client.receive(error),
+            Err(FramingError::Remote { message }) if message == "unknown user"
+        ));
+        assert!(!client.is_complete());
+        assert!(matches!(
+            client.send(Vec::new()),
+            Err(FramingError::Finished)
+        ));
+    }
+
+    #[test]
+    fn frame_round_trips_and_rejects_truncation() {
+        let frame = Frame {
+            flow_id: vec![7u8; FLOW_ID_LEN],
+            step: FlowStep::RegistrationResponse,
+            error: None,
+            payload: b"payload".to_vec(),
+        };
+        let bytes = encode_frame(frame.clone()).unwrap();
+        assert_eq!(decode_frame(&bytes).unwrap(), frame);
+        assert!(matches!(
+            decode_frame(&bytes[..bytes.len() - 1]),
+            Err(FramingError::Malformed(_))
+        ));
+    }
+}
@@ -0,0 +1,381 @@
+// SPDX-FileCopyrightText: 2026 Digg - Agency for Digital Government
+//
+// SPDX-License-Identifier: EUPL-1.2
+
+//! This module exports prime-order group arithmetic for P-256 and Ristretto255, so the
+//! points returned by the `hash2curve` functions can be used in blinding schemes.
+//!
+//! Points use the encodings of the `hash2curve` functions: compressed SEC1 (33 bytes) for
+//! P-256 and the canonical 32-byte encoding for Ristretto255. P-256 inputs may also be
+//! uncompressed SEC1, and the identity is encoded as the single byte `0x00` for P-256 and
+//! as 32 zero bytes for Ristretto255. Scalars are 32-byte canonical integers modulo the
+//! group order, big-endian for P-256 and little-endian for Ristretto255, as in RFC 9497.
+
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use p256::elliptic_curve::{
+    ff::PrimeField,
+    sec1::{FromEncodedPoint, ToEncodedPoint},
+};
+use rand::{rngs::OsRng, RngCore};
+
+#[derive(Debug, thiserror::Error, uniffi::Error)]
+pub enum GroupError {
+    #[error("Invalid point encoding")]
+    InvalidPoint,
+    #[error("Invalid scalar encoding")]
+    InvalidScalar,
+    #[error("Invalid parameter: {0}")]
+    InvalidParameter(String),
+}
+
+/// The prime-order groups that can be used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum GroupKind {
+    P256,
+    Ristretto255,
+}
+
+/// A decoded point of either group.
+enum Point {
+    P256(p256::ProjectivePoint),
+    Ristretto255(RistrettoPoint),
+}
+
+/// A decoded scalar of either group.
+enum Scalar {
+    P256(p256::Scalar),
+    Ristretto255(curve25519_dalek::Scalar),
+}
+
+fn decode_point(group: GroupKind, bytes: &[u8]) -> Result<Point, GroupError> {
+    match group {
+        GroupKind::P256 => {
+            let encoded =
+                p256::EncodedPoint::from_bytes(bytes).map_err(|_| GroupError::InvalidPoint)?;
+            Option::<p256::AffinePoint>::from(p256::AffinePoint::from_encoded_point(&encoded))
+                .map(|point| Point::P256(point.into()))
+                .ok_or(GroupError::InvalidPoint)
+        }
+        GroupKind::Ristretto255 => CompressedRistretto::from_slice(bytes)
+            .ok()
+            .and_then(|compressed| compressed.decompress())
+            .map(Point::Ristretto255)
+            .ok_or(GroupError::InvalidPoint),
+    }
+}
+
+fn encode_point(point: Point) -> Vec<u8> {
+    match point {
+        Point::P256(point) => point.to_encoded_point(true).as_bytes().to_vec(),
+        Point::Ristretto255(point) => point.compress().to_bytes().to_vec(),
+    }
+}
+
+fn decode_scalar(group: GroupKind, bytes: &[u8]) -> Result<Scalar, GroupError> {
+    let bytes: [u8; 32] = bytes.try_into().map_err(|_| GroupError::InvalidScalar)?;
+    match group {
+        GroupKind::P256 => Option::from(p256::Scalar::from_repr(bytes.into()))
+            .map(Scalar::P256)
+            .ok_or(GroupError::InvalidScalar),
+        GroupKind::Ristretto255 => {
+            Option::from(curve25519_dalek::Scalar::from_canonical_bytes(bytes))
+                .map(Scalar::Ristretto255)
+                .ok_or(GroupError::InvalidScalar)
+        }
+    }
+}
+
+fn encode_scalar(scalar: Scalar) -> Vec<u8> {
+    match scalar {
+        Scalar::P256(scalar) => scalar.to_repr().to_vec(),
+        Scalar::Ristretto255(scalar) => scalar.to_bytes().to_vec(),
+    }
+}
+
+/// Adds two points.
+#[uniffi::export]
+pub fn group_point_add(group: GroupKind, a: Vec<u8>, b: Vec<u8>) -> Result<Vec<u8>, GroupError> {
+    let sum = match (decode_point(group, &a)?, decode_point(group, &b)?) {
+        (Point::P256(a), Point::P256(b)) => Point::P256(a + b),
+        (Point::Ristretto255(a), Point::Ristretto255(b)) => Point::Ristretto255(a + b),
+        _ => unreachable!("both points are decoded for the same group"),
+    };
+    Ok(encode_point(sum))
+}
+
+/// Negates a point.
+#[uniffi::export]
+pub fn group_point_negate(group: GroupKind, point: Vec<u8>) -> Result<Vec<u8>, GroupError> {
+    let negated = match decode_point(group, &point)? {
+        Point::P256(point) => Point::P256(-point),
+        Point::Ristretto255(point) => Point::Ristretto255(-point),
+    };
+    Ok(encode_point(negated))
+}
+
+/// Multiplies a point by a scalar.
+#[uniffi::export]
+pub fn group_scalar_mul(
+    group: GroupKind,
+    scalar: Vec<u8>,
+    point: Vec<u8>,
+) -> Result<Vec<u8>, GroupError> {
+    let product = match (decode_scalar(group, &scalar)?, decode_point(group, &point)?) {
+        (Scalar::P256(scalar), Point::P256(point)) => Point::P256(point * scalar),
+        (Scalar::Ristretto255(scalar), Point::Ristretto255(point)) => {
+            Point::Ristretto255(point * scalar)
+        }
+        _ => unreachable!("the scalar and point are decoded for the same group"),
+    };
+    Ok(encode_point(product))
+}
+
+/// Multiplies the group's generator by a scalar, e.g. to compute a public key.
+#[uniffi::export]
+pub fn group_scalar_mul_base(group: GroupKind, scalar: Vec<u8>) -> Result<Vec<u8>, GroupError> {
+    let product = match decode_scalar(group, &scalar)? {
+        Scalar::P256(scalar) => Point::P256(p256::ProjectivePoint::GENERATOR * scalar),
+        Scalar::Ristretto255(scalar) => Point::Ristretto255(RistrettoPoint::mul_base(&scalar)),
+    };
+    Ok(encode_point(product))
+}
+
+/// Returns whether `point` is a valid encoding of an element of the group.
+#[uniffi::export]
+pub fn group_point_is_valid(group: GroupKind, point: Vec<u8>) -> bool {
+    decode_point(group, &point).is_ok()
+}
+
+/// Validates a point and returns its uncompressed encoding.
+///
+/// For P-256 this is the 65-byte uncompressed SEC1 encoding. Ristretto255 has a single
+/// 32-byte encoding, which is returned as is once validated.
+#[uniffi::export]
+pub fn group_point_decompress(group: GroupKind, point: Vec<u8>) -> Result<Vec<u8>, GroupError> {
+    match decode_point(group, &point)? {
+        Point::P256(point) => Ok(point.to_encoded_point(false).as_bytes().to_vec()),
+        point => Ok(encode_point(point)),
+    }
+}
+
+/// Generates a uniformly random non-zero scalar, e.g. a blinding factor.
+#[uniffi::export]
+pub fn group_scalar_random(group: GroupKind) -> Vec<u8> {
+    match group {
+        GroupKind::P256 => encode_scalar(Scalar::P256(*p256::NonZeroScalar::random(&mut OsRng))),
+        GroupKind::Ristretto255 => loop {
+            let mut wide = [0u8; 64];
+            OsRng.fill_bytes(&mut wide);
+            let scalar = curve25519_dalek::Scalar::from_bytes_mod_order_wide(&wide);
+            if scalar != curve25519_dalek::Scalar::ZERO {
+                break encode_scalar(Scalar::Ristretto255(scalar));
+            }
+        },
+    }
+}
+
+/// Reduces an integer of up to 64 bytes modulo the group order.
+///
+/// The integer is read in the group's scalar byte order, so 64 uniformly random or hashed
+/// bytes give a scalar with negligible bias.
+#[uniffi::export]
+pub fn group_scalar_reduce(group: GroupKind, bytes: Vec<u8>) -> Result<Vec<u8>, GroupError> {
+    use p256::elliptic_curve::bigint::{Encoding, NonZero, U512};
+
+    if bytes.len() > 64 {
+        return Err(GroupError::InvalidParameter(
+            "at most 64 bytes can be reduced".into(),
+        ));
+    }
+    let scalar = match group {
+        GroupKind::P256 => {
+            let order = U512::from_be_hex(&format!(
+                "{:0>128}",
+                "ffffffff00000000ffffffffffffffffbce6faada7179e84f3b9cac2fc632551"
+            ));
+            let mut wide = [0u8; 64];
+            wide[64 - bytes.len()..].copy_from_slice(&bytes);
+            let reduced = U512::from_be_bytes(wide).rem(&NonZero::new(order).unwrap());
+            let repr: [u8; 32] = reduced.to_be_bytes()[32..].try_into().unwrap();
+            Scalar::P256(p256::Scalar::from_repr(repr.into()).unwrap())
+        }
+        GroupKind::Ristretto255 => {
+            let mut wide = [0u8; 64];
+            wide[..bytes.len()].copy_from_slice(&bytes);
+            Scalar::Ristretto255(curve25519_dalek::Scalar::from_bytes_mod_order_wide(&wide))
+        }
+    };
+    Ok(encode_scalar(scalar))
+}
+
+/// Inverts a non-zero scalar modulo the group order, e.g. to remove a blinding factor.
+#[uniffi::export]
+pub fn group_scalar_invert(group: GroupKind, scalar: Vec<u8>) -> Result<Vec<u8>, GroupError> {
+    let inverse = match decode_scalar(group, &scalar)? {
+        Scalar::P256(scalar) => Option::from(scalar.invert())
+            .map(Scalar::P256)
+            .ok_or(GroupError::InvalidScalar)?,
+        Scalar::Ristretto255(scalar) if scalar != curve25519_dalek::Scalar::ZERO => {
+            Scalar::Ristretto255(scalar.invert())
+        }
+        Scalar::Ristretto255(_) => return Err(GroupError::InvalidScalar),
+    };
+    Ok(encode_scalar(inverse))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash2curve::{hash_to_curve_p256_sha256, hash_to_curve_ristretto255_sha512};
+
+    fn hashed_point(group: GroupKind, input: &[u8]) -> Vec<u8> {
+        match group {
+            GroupKind::P256 => hash_to_curve_p256_sha256(input.to_vec(), b"group-test".to_vec()),
+            GroupKind::Ristretto255 => {
+                hash_to_curve_ristretto255_sha512(input.to_vec(), b"group-test".to_vec())
+            }
+        }
+        .unwrap()
+    }
+
+    #[test]
+    fn test_blind_unblind_round_trip() {
+        for group in [GroupKind::P256, GroupKind::Ristretto255] {
+            let point = hashed_point(group, b"input");
+            let blind = group_scalar_random(group);
+            let blinded = group_scalar_mul(group, blind.clone(), point.clone()).unwrap();
+            assert_ne!(blinded, point);
+
+            let unblind = group_scalar_invert(group, blind).unwrap();
+            assert_eq!(group_scalar_mul(group, unblind, blinded).unwrap(), point);
+        }
+    }
+
+    #[test]
+    fn test_add_and_negate() {
+        for group in [GroupKind::P256, GroupKind::Ristretto255] {
+            let a = hashed_point(group, b"a");
+            let b = hashed_point(group, b"b");
+            let sum = group_point_add(group, a.clone(), b.clone()).unwrap();
+            assert_eq!(sum, group_point_add(group, b.clone(), a.clone()).unwrap());
+
+            let neg_b = group_point_negate(group, b).unwrap();
+            assert_eq!(group_point_add(group, sum, neg_b).unwrap(), a);
+
+            // a + (-a) is the identity, which is itself a valid point.
+            let identity =
+                group_point_add(group, a.clone(), group_point_negate(group, a).unwrap()).unwrap();
+            let expected_identity = match group {
+                GroupKind::P256 => vec![0u8],
+                GroupKind::Ristretto255 => vec![0u8; 32],
+            };
+            assert_eq!(identity, expected_identity);
+            assert!(group_point_is_valid(group, identity));
+        }
+    }
+
+    #[test]
+    fn test_scalar_mul_distributes() {
+        for group in [GroupKind::P256, GroupKind::Ristretto255] {
+            let s = group_scalar_random(group);
+            let a = hashed_point(group, b"a");
+            let b = hashed_point(group, b"b");
+            let sa = group_scalar_mul(group, s.clone(), a.clone()).unwrap();
+            let sb = group_scalar_mul(group, s.clone(), b.clone()).unwrap();
+            assert_eq!(
+                group_scalar_mul(group, s, group_point_add(group, a, b).unwrap()).unwrap(),
+                group_point_add(group, sa, sb).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_scalar_mul_base_matches_oprf_public_key() {
+        use crate::oprf::{oprf_generate_key_pair, OprfSuite};
+
+        for (group, suite) in [
+            (GroupKind::P256, OprfSuite::P256Sha256),
+            (GroupKind::Ristretto255, OprfSuite::Ristretto255Sha512),
+        ] {
+            let key_pair = oprf_generate_key_pair(suite).unwrap();
+            assert_eq!(
+                group_scalar_mul_base(group, key_pair.private_key).unwrap(),
+                key_pair.public_key
+            );
+        }
+    }
+
+    #[test]
+    fn test_decompress_and_validate() {
+        let point = hashed_point(GroupKind::P256, b"input");
+        let uncompressed = group_point_decompress(GroupKind::P256, point.clone()).unwrap();
+        assert_eq!(uncompressed.len(), 65);
+        assert_eq!(uncompressed[0], 0x04);
+        assert_eq!(&uncompressed[1..33], &point[1..]);
+        // Uncompressed inputs are accepted and compressed on output.
+        let neg = group_point_negate(GroupKind::P256, uncompressed).unwrap();
+        assert_eq!(group_point_negate(GroupKind::P256, neg).unwrap(), point);
+
+        let point = hashed_point(GroupKind::Ristretto255, b"input");
+        assert_eq!(
+            group_point_decompress(GroupKind::Ristretto255, point.clone()).unwrap(),
+            point
+        );
+
+        // (1, 1) is not on P-256, and all 0xff bytes are not a canonical Ristretto255 encoding.
+        let mut not_on_curve = vec![0x04];
+        not_on_curve.extend([0u8; 31]);
+        not_on_curve.push(1);
+        not_on_curve.extend([0u8; 31]);
+        not_on_curve.push(1);
+        assert!(!group_point_is_valid(GroupKind::P256, not_on_curve.clone()));
+        assert!(matches!(
+            group_point_decompress(GroupKind::P256, not_on_curve),
+            Err(GroupError::InvalidPoint)
+        ));
+        assert!(!group_point_is_valid(
+            GroupKind::Ristretto255,
+            vec![0xff; 32]
+        ));
+        assert!(!group_point_is_valid(GroupKind::Ristretto255, vec![0; 31]));
+    }
+
+    #[test]
+    fn test_scalar_reduce_and_validation() {
+        // The group orders reduce to zero, and are not canonical scalars themselves.
+        let p256_order =
+            hex::decode("ffffffff00000000ffffffffffffffffbce6faada7179e84f3b9cac2fc632551")
+                .unwrap();
+        let ristretto_order =
+            hex::decode("edd3f55c1a631258d69cf7a2def9de1400000000000000000000000000000010")
+                .unwrap();
+        for (group, order) in [
+            (GroupKind::P256, p256_order),
+            (GroupKind::Ristretto255, ristretto_order),
+        ] {
+            assert_eq!(
+                group_scalar_reduce(group, order.clone()).unwrap(),
+                vec![0u8; 32]
+            );
+            assert!(matches!(
+                group_scalar_mul_base(group, order),
+                Err(GroupError::InvalidScalar)
+            ));
+            assert!(matches!(
+                group_scalar_invert(group, vec![0u8; 32]),
+                Err(GroupError::InvalidScalar)
+            ));
+            assert!(group_scalar_reduce(group, vec![0xff; 65]).is_err());
+        }
+
+        // Short inputs are read in each group's byte order.
+        assert_eq!(
+            group_scalar_reduce(GroupKind::P256, vec![1]).unwrap()[31],
+            1
+        );
+        assert_eq!(
+            group_scalar_reduce(GroupKind::Ristretto255, vec![1]).unwrap()[0],
+            1
+        );
+    }
+}
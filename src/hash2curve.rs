@@ -8,6 +8,33 @@ use voprf::Group;
 pub enum Hash2CurveError {
     #[error("Hash to curve failed: {0}")]
     InternalError(String),
+    #[error("Domain separation tag must not be empty")]
+    EmptyDst,
+    #[error("Unsupported suite: {0}")]
+    UnsupportedSuite(String),
+    #[error("Invalid input: {0}")]
+    InvalidInput(String),
+}
+
+/// Prefix of the hash that replaces DSTs longer than 255 bytes (RFC 9380, Section 5.3.3).
+const OVERSIZE_DST_SALT: &[u8] = b"H2C-OVERSIZE-DST-";
+
+/// The longest DST that is used as is rather than hashed.
+const MAX_DST_LEN: usize = 255;
+
+/// The length of a DST given as parts, which are hashed as their concatenation.
+fn dst_len(dsts: &[&[u8]]) -> usize {
+    dsts.iter().map(|dst| dst.len()).sum()
+}
+
+/// Rejects empty DSTs, which RFC 9380 (Section 3.1) requires to be non-empty.
+///
+/// DSTs longer than 255 bytes are hashed by `expand_message` as Section 5.3.3 specifies.
+fn check_dst(dsts: &[&[u8]]) -> Result<(), Hash2CurveError> {
+    if dst_len(dsts) == 0 {
+        return Err(Hash2CurveError::EmptyDst);
+    }
+    Ok(())
 }
 
 /// Hashes input data to a curve point using the hash_to_curve algorithm from RFC 9380.
@@ -28,6 +55,8 @@ pub fn hash_to_curve_ristretto255_sha512(
 ) -> Result<Vec<u8>, Hash2CurveError> {
     use voprf::Ristretto255;
 
+    check_dst(&[&dst])?;
+
     let input_slice = input.as_slice();
     let dst_slice = dst.as_slice();
 
@@ -58,6 +87,8 @@ pub fn hash_to_curve_p256_sha256(
 ) -> Result<Vec<u8>, Hash2CurveError> {
     use p256::elliptic_curve::sec1::ToEncodedPoint;
 
+    check_dst(&[&dst])?;
+
     let input_slice = input.as_slice();
     let dst_slice = dst.as_slice();
 
@@ -70,6 +101,494 @@ pub fn hash_to_curve_p256_sha256(
     Ok(point.to_encoded_point(true).as_bytes().to_vec())
 }
 
+/// Output formats for [`hash_to_curve_p256_sha256_with_format`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum PointFormat {
+    /// 33-byte compressed SEC1 encoding
+    Compressed,
+    /// 65-byte uncompressed SEC1 encoding
+    Uncompressed,
+    /// Separate affine x and y coordinates
+    Coordinates,
+}
+
+/// A point in the [`PointFormat`] it was requested in.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Enum)]
+pub enum FormattedPoint {
+    /// SEC1 encoding, compressed or uncompressed
+    Sec1 { bytes: Vec<u8> },
+    /// Affine coordinates as big-endian integers of the field's byte length
+    Coordinates { x: Vec<u8>, y: Vec<u8> },
+}
+
+/// Hashes input data to a P-256 point like [`hash_to_curve_p256_sha256`], in a chosen format.
+///
+/// # Arguments
+/// * `input` - The input data to hash
+/// * `dst` - Domain separation tag
+/// * `format` - The output format of the point
+///
+/// # Returns
+/// The point as compressed (33 bytes) or uncompressed (65 bytes) SEC1, or as its
+/// 32-byte affine x and y coordinates
+#[uniffi::export]
+pub fn hash_to_curve_p256_sha256_with_format(
+    input: Vec<u8>,
+    dst: Vec<u8>,
+    format: PointFormat,
+) -> Result<FormattedPoint, Hash2CurveError> {
+    use p256::elliptic_curve::sec1::{Coordinates, ToEncodedPoint};
+
+    check_dst(&[&dst])?;
+    let point = p256::NistP256::hash_to_curve::<sha2::Sha256>(&[&input], &[&dst])
+        .map_err(|e| Hash2CurveError::InternalError(format!("{:?}", e)))?;
+
+    let encoded = point.to_encoded_point(format == PointFormat::Compressed);
+    match (format, encoded.coordinates()) {
+        (PointFormat::Coordinates, Coordinates::Uncompressed { x, y }) => {
+            Ok(FormattedPoint::Coordinates {
+                x: x.to_vec(),
+                y: y.to_vec(),
+            })
+        }
+        (PointFormat::Coordinates, _) => Err(Hash2CurveError::InternalError(
+            "Point has no affine coordinates".into(),
+        )),
+        _ => Ok(FormattedPoint::Sec1 {
+            bytes: encoded.as_bytes().to_vec(),
+        }),
+    }
+}
+
+/// The RFC 9380 suites supported by [`hash_to_curve`], named after their suite IDs.
+///
+/// The edwards25519 and curve25519 suites need the `hash2curve-25519` feature; without it
+/// they fail with `UnsupportedSuite`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum Hash2CurveSuite {
+    /// `P256_XMD:SHA-256_SSWU_RO_`
+    P256Sha256Ro,
+    /// `P256_XMD:SHA-256_SSWU_NU_`
+    P256Sha256Nu,
+    /// `P384_XMD:SHA-384_SSWU_RO_`
+    P384Sha384Ro,
+    /// `P384_XMD:SHA-384_SSWU_NU_`
+    P384Sha384Nu,
+    /// `P521_XMD:SHA-512_SSWU_RO_`
+    P521Sha512Ro,
+    /// `P521_XMD:SHA-512_SSWU_NU_`
+    P521Sha512Nu,
+    /// `secp256k1_XMD:SHA-256_SSWU_RO_`
+    Secp256k1Sha256Ro,
+    /// `secp256k1_XMD:SHA-256_SSWU_NU_`
+    Secp256k1Sha256Nu,
+    /// `edwards25519_XMD:SHA-512_ELL2_RO_`
+    Edwards25519Sha512Ro,
+    /// `edwards25519_XMD:SHA-512_ELL2_NU_`
+    Edwards25519Sha512Nu,
+    /// `curve25519_XMD:SHA-512_ELL2_RO_`
+    Curve25519Sha512Ro,
+    /// `curve25519_XMD:SHA-512_ELL2_NU_`
+    Curve25519Sha512Nu,
+}
+
+/// Hashes input data to a curve point with one of the RFC 9380 suites.
+///
+/// For the `_NU_` suites this is the non-uniform `encode_to_curve`, as their suite IDs specify.
+///
+/// # Arguments
+/// * `suite` - The suite to hash with
+/// * `input` - The input data to hash
+/// * `dst` - Domain separation tag
+///
+/// # Returns
+/// The serialized point: compressed SEC1 for the Weierstrass curves (33, 49 or 67 bytes),
+/// the 32-byte compressed Edwards y-coordinate for edwards25519 and the 32-byte
+/// Montgomery u-coordinate (as used by X25519) for curve25519
+#[uniffi::export]
+pub fn hash_to_curve(
+    suite: Hash2CurveSuite,
+    input: Vec<u8>,
+    dst: Vec<u8>,
+) -> Result<Vec<u8>, Hash2CurveError> {
+    hash_parts_to_curve(suite, &[&input], &[&dst])
+}
+
+/// The 2-byte big-endian lengths of the input parts.
+fn part_lengths(parts: &[Vec<u8>]) -> Result<Vec<[u8; 2]>, Hash2CurveError> {
+    parts
+        .iter()
+        .map(|part| {
+            u16::try_from(part.len())
+                .map(u16::to_be_bytes)
+                .map_err(|_| {
+                    Hash2CurveError::InvalidInput("an input part is longer than 65535 bytes".into())
+                })
+        })
+        .collect()
+}
+
+/// The input parts, each preceded by its length, as slices to hash without copying them.
+fn length_prefixed<'a>(parts: &'a [Vec<u8>], lengths: &'a [[u8; 2]]) -> Vec<&'a [u8]> {
+    lengths
+        .iter()
+        .zip(parts)
+        .flat_map(|(len, part)| [&len[..], part.as_slice()])
+        .collect()
+}
+
+/// Hashes input data given as a list of parts to a curve point with one of the RFC 9380 suites.
+///
+/// Each part of `input` is hashed with a 2-byte big-endian length prefix, so e.g.
+/// `["ab", "c"]` and `["a", "bc"]` hash to different points; the result is that of
+/// [`hash_to_curve`] on the concatenation of the length-prefixed parts. The parts of `dst`
+/// are a tag split for convenience and are hashed as their concatenation. Neither is
+/// copied into one buffer.
+///
+/// # Arguments
+/// * `suite` - The suite to hash with
+/// * `input_parts` - The parts of the input data to hash
+/// * `dst_parts` - The parts of the domain separation tag
+///
+/// # Returns
+/// The serialized point, as for [`hash_to_curve`]
+#[uniffi::export]
+pub fn hash_to_curve_parts(
+    suite: Hash2CurveSuite,
+    input_parts: Vec<Vec<u8>>,
+    dst_parts: Vec<Vec<u8>>,
+) -> Result<Vec<u8>, Hash2CurveError> {
+    let lengths = part_lengths(&input_parts)?;
+    let msgs = length_prefixed(&input_parts, &lengths);
+    let dsts: Vec<&[u8]> = dst_parts.iter().map(Vec::as_slice).collect();
+    hash_parts_to_curve(suite, &msgs, &dsts)
+}
+
+/// Hashes input data given as a list of parts to a Ristretto255 point, like
+/// [`hash_to_curve_ristretto255_sha512`] on the concatenation of the length-prefixed input
+/// parts and the concatenation of the DST parts.
+///
+/// The parts are encoded as for [`hash_to_curve_parts`].
+///
+/// # Arguments
+/// * `input_parts` - The parts of the input data to hash
+/// * `dst_parts` - The parts of the domain separation tag
+///
+/// # Returns
+/// The serialized compressed curve point as a 32-byte array
+#[uniffi::export]
+pub fn hash_to_curve_ristretto255_sha512_parts(
+    input_parts: Vec<Vec<u8>>,
+    dst_parts: Vec<Vec<u8>>,
+) -> Result<Vec<u8>, Hash2CurveError> {
+    use voprf::Ristretto255;
+
+    let lengths = part_lengths(&input_parts)?;
+    let msgs = length_prefixed(&input_parts, &lengths);
+    let dsts: Vec<&[u8]> = dst_parts.iter().map(Vec::as_slice).collect();
+    check_dst(&dsts)?;
+
+    let point = Ristretto255::hash_to_curve::<sha2::Sha512>(&msgs, &dsts)
+        .map_err(|e| Hash2CurveError::InternalError(format!("{:?}", e)))?;
+
+    Ok(point.compress().to_bytes().to_vec())
+}
+
+fn hash_parts_to_curve(
+    suite: Hash2CurveSuite,
+    msgs: &[&[u8]],
+    dsts: &[&[u8]],
+) -> Result<Vec<u8>, Hash2CurveError> {
+    use p256::elliptic_curve::{
+        hash2curve::{ExpandMsgXmd, GroupDigest},
+        sec1::ToEncodedPoint,
+    };
+
+    fn err(e: p256::elliptic_curve::Error) -> Hash2CurveError {
+        Hash2CurveError::InternalError(format!("{:?}", e))
+    }
+
+    check_dst(dsts)?;
+
+    let point = match suite {
+        Hash2CurveSuite::P256Sha256Ro => {
+            type Xmd = ExpandMsgXmd<sha2::Sha256>;
+            let point = p256::NistP256::hash_from_bytes::<Xmd>(msgs, dsts).map_err(err)?;
+            point.to_encoded_point(true).as_bytes().to_vec()
+        }
+        Hash2CurveSuite::P256Sha256Nu => {
+            type Xmd = ExpandMsgXmd<sha2::Sha256>;
+            let point = p256::NistP256::encode_from_bytes::<Xmd>(msgs, dsts).map_err(err)?;
+            point.to_encoded_point(true).as_bytes().to_vec()
+        }
+        Hash2CurveSuite::P384Sha384Ro => {
+            type Xmd = ExpandMsgXmd<sha2::Sha384>;
+            let point = p384::NistP384::hash_from_bytes::<Xmd>(msgs, dsts).map_err(err)?;
+            point.to_encoded_point(true).as_bytes().to_vec()
+        }
+        Hash2CurveSuite::P384Sha384Nu => {
+            type Xmd = ExpandMsgXmd<sha2::Sha384>;
+            let point = p384::NistP384::encode_from_bytes::<Xmd>(msgs, dsts).map_err(err)?;
+            point.to_encoded_point(true).as_bytes().to_vec()
+        }
+        Hash2CurveSuite::P521Sha512Ro => {
+            type Xmd = ExpandMsgXmd<sha2::Sha512>;
+            let point = p521::NistP521::hash_from_bytes::<Xmd>(msgs, dsts).map_err(err)?;
+            point.to_affine().to_encoded_point(true).as_bytes().to_vec()
+        }
+        Hash2CurveSuite::P521Sha512Nu => {
+            type Xmd = ExpandMsgXmd<sha2::Sha512>;
+            let point = p521::NistP521::encode_from_bytes::<Xmd>(msgs, dsts).map_err(err)?;
+            point.to_affine().to_encoded_point(true).as_bytes().to_vec()
+        }
+        Hash2CurveSuite::Secp256k1Sha256Ro => {
+            type Xmd = ExpandMsgXmd<sha2::Sha256>;
+            let point = k256::Secp256k1::hash_from_bytes::<Xmd>(msgs, dsts).map_err(err)?;
+            point.to_affine().to_encoded_point(true).as_bytes().to_vec()
+        }
+        Hash2CurveSuite::Secp256k1Sha256Nu => {
+            type Xmd = ExpandMsgXmd<sha2::Sha256>;
+            let point = k256::Secp256k1::encode_from_bytes::<Xmd>(msgs, dsts).map_err(err)?;
+            point.to_affine().to_encoded_point(true).as_bytes().to_vec()
+        }
+        Hash2CurveSuite::Edwards25519Sha512Ro
+        | Hash2CurveSuite::Edwards25519Sha512Nu
+        | Hash2CurveSuite::Curve25519Sha512Ro
+        | Hash2CurveSuite::Curve25519Sha512Nu => hash_parts_to_25519(suite, msgs, dsts)?,
+    };
+
+    Ok(point)
+}
+
+/// Hashes to edwards25519 or curve25519, with curve25519-dalek 5, which implements the
+/// RFC 9380 maps for them on the `digest` 0.11 line.
+#[cfg(feature = "hash2curve-25519")]
+fn hash_parts_to_25519(
+    suite: Hash2CurveSuite,
+    msgs: &[&[u8]],
+    dsts: &[&[u8]],
+) -> Result<Vec<u8>, Hash2CurveError> {
+    use curve25519_dalek_next::EdwardsPoint;
+
+    // curve25519-dalek panics on DSTs longer than 255 bytes instead of hashing them,
+    // so apply the rule of RFC 9380, Section 5.3.3 before passing them on.
+    let oversize_dst;
+    let dsts = if dst_len(dsts) > MAX_DST_LEN {
+        use sha2::Digest;
+
+        let mut hasher = sha2::Sha512::new_with_prefix(OVERSIZE_DST_SALT);
+        dsts.iter().for_each(|dst| hasher.update(dst));
+        oversize_dst = hasher.finalize();
+        &[&oversize_dst[..]]
+    } else {
+        dsts
+    };
+    let point = match suite {
+        Hash2CurveSuite::Edwards25519Sha512Ro | Hash2CurveSuite::Curve25519Sha512Ro => {
+            EdwardsPoint::hash_to_curve::<sha2_next::Sha512>(msgs, dsts)
+        }
+        _ => EdwardsPoint::encode_to_curve::<sha2_next::Sha512>(msgs, dsts),
+    };
+    // The curve25519 maps are the edwards25519 ones before the birational map to
+    // edwards25519 (RFC 9380, Section 6.8.2), so the points correspond.
+    Ok(match suite {
+        Hash2CurveSuite::Curve25519Sha512Ro | Hash2CurveSuite::Curve25519Sha512Nu => {
+            point.to_montgomery().to_bytes().to_vec()
+        }
+        _ => point.compress().to_bytes().to_vec(),
+    })
+}
+
+#[cfg(not(feature = "hash2curve-25519"))]
+fn hash_parts_to_25519(
+    suite: Hash2CurveSuite,
+    _msgs: &[&[u8]],
+    _dsts: &[&[u8]],
+) -> Result<Vec<u8>, Hash2CurveError> {
+    Err(Hash2CurveError::UnsupportedSuite(format!(
+        "{:?} requires the hash2curve-25519 feature",
+        suite
+    )))
+}
+
+/// The hash functions usable with [`expand_message_xmd`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum Hash2CurveHash {
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+/// The extendable-output functions usable with [`expand_message_xof`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum Hash2CurveXof {
+    Shake128,
+    Shake256,
+}
+
+fn expand_message<'a, E>(
+    msg: &[u8],
+    dsts: &'a [&'a [u8]],
+    len: u32,
+) -> Result<Vec<u8>, Hash2CurveError>
+where
+    E: p256::elliptic_curve::hash2curve::ExpandMsg<'a>,
+{
+    use p256::elliptic_curve::hash2curve::Expander;
+
+    let len = usize::try_from(len).map_err(|e| Hash2CurveError::InternalError(e.to_string()))?;
+    let mut expander = E::expand_message(&[msg], dsts, len)
+        .map_err(|e| Hash2CurveError::InternalError(format!("{:?}", e)))?;
+    let mut out = vec![0u8; len];
+    expander.fill_bytes(&mut out);
+    Ok(out)
+}
+
+/// Expands a message into `len` uniformly random bytes with `expand_message_xmd` from RFC 9380.
+///
+/// # Arguments
+/// * `hash` - The hash function to expand with
+/// * `msg` - The message to expand
+/// * `dst` - Domain separation tag
+/// * `len` - The number of bytes to output, at most 255 hash outputs and 65535 bytes
+///
+/// # Returns
+/// The `len` output bytes
+#[uniffi::export]
+pub fn expand_message_xmd(
+    hash: Hash2CurveHash,
+    msg: Vec<u8>,
+    dst: Vec<u8>,
+    len: u32,
+) -> Result<Vec<u8>, Hash2CurveError> {
+    use p256::elliptic_curve::hash2curve::ExpandMsgXmd;
+
+    check_dst(&[&dst])?;
+    let dsts: &[&[u8]] = &[&dst];
+    match hash {
+        Hash2CurveHash::Sha256 => expand_message::<ExpandMsgXmd<sha2::Sha256>>(&msg, dsts, len),
+        Hash2CurveHash::Sha384 => expand_message::<ExpandMsgXmd<sha2::Sha384>>(&msg, dsts, len),
+        Hash2CurveHash::Sha512 => expand_message::<ExpandMsgXmd<sha2::Sha512>>(&msg, dsts, len),
+    }
+}
+
+/// Expands a message into `len` uniformly random bytes with `expand_message_xof` from RFC 9380.
+///
+/// # Arguments
+/// * `xof` - The extendable-output function to expand with
+/// * `msg` - The message to expand
+/// * `dst` - Domain separation tag
+/// * `len` - The number of bytes to output, at most 65535
+///
+/// # Returns
+/// The `len` output bytes
+#[uniffi::export]
+pub fn expand_message_xof(
+    xof: Hash2CurveXof,
+    msg: Vec<u8>,
+    dst: Vec<u8>,
+    len: u32,
+) -> Result<Vec<u8>, Hash2CurveError> {
+    use p256::elliptic_curve::hash2curve::ExpandMsgXof;
+    use sha3::digest::{ExtendableOutput, Update};
+
+    check_dst(&[&dst])?;
+    // Long DSTs are hashed to 2k bits for the XOF's security level k of 128 or 256 bits
+    // (RFC 9380, Section 5.3.3), whereas `ExpandMsgXof` always hashes them to 32 bytes.
+    let dst = if dst.len() > MAX_DST_LEN {
+        let oversize_dst = match xof {
+            Hash2CurveXof::Shake128 => sha3::Shake128::default()
+                .chain(OVERSIZE_DST_SALT)
+                .chain(&dst)
+                .finalize_boxed(32),
+            Hash2CurveXof::Shake256 => sha3::Shake256::default()
+                .chain(OVERSIZE_DST_SALT)
+                .chain(&dst)
+                .finalize_boxed(64),
+        };
+        oversize_dst.into_vec()
+    } else {
+        dst
+    };
+    let dsts: &[&[u8]] = &[&dst];
+    match xof {
+        Hash2CurveXof::Shake128 => expand_message::<ExpandMsgXof<sha3::Shake128>>(&msg, dsts, len),
+        Hash2CurveXof::Shake256 => expand_message::<ExpandMsgXof<sha3::Shake256>>(&msg, dsts, len),
+    }
+}
+
+/// Hashes input data to elements of a suite's base field with `hash_to_field` from RFC 9380.
+///
+/// The `_RO_` suites hash to two field elements and the `_NU_` suites to one, but any
+/// `count` may be requested. The RO and NU suites of a curve share the same field and
+/// expansion, so they give the same elements.
+///
+/// # Arguments
+/// * `suite` - The suite whose field, hash and security level to use
+/// * `input` - The input data to hash
+/// * `dst` - Domain separation tag
+/// * `count` - The number of field elements to output
+///
+/// # Returns
+/// The field elements as big-endian integers of the field's byte length
+/// (32 bytes, 48 bytes for P-384 and 66 bytes for P-521)
+#[uniffi::export]
+pub fn hash_to_field(
+    suite: Hash2CurveSuite,
+    input: Vec<u8>,
+    dst: Vec<u8>,
+    count: u32,
+) -> Result<Vec<Vec<u8>>, Hash2CurveError> {
+    use p256::elliptic_curve::bigint::{Encoding, NonZero, U1024};
+
+    // (field modulus p, hash function, L = ceil((ceil(log2(p)) + k) / 8)) per RFC 9380, Section 8.
+    let (modulus, hash, l) = match suite {
+        Hash2CurveSuite::P256Sha256Ro | Hash2CurveSuite::P256Sha256Nu => (
+            "ffffffff00000001000000000000000000000000ffffffffffffffffffffffff",
+            Hash2CurveHash::Sha256,
+            48,
+        ),
+        Hash2CurveSuite::P384Sha384Ro | Hash2CurveSuite::P384Sha384Nu => (
+            "fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffeffffffff0000000000000000ffffffff",
+            Hash2CurveHash::Sha384,
+            72,
+        ),
+        Hash2CurveSuite::P521Sha512Ro | Hash2CurveSuite::P521Sha512Nu => (
+            "01ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+            Hash2CurveHash::Sha512,
+            98,
+        ),
+        Hash2CurveSuite::Secp256k1Sha256Ro | Hash2CurveSuite::Secp256k1Sha256Nu => (
+            "fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f",
+            Hash2CurveHash::Sha256,
+            48,
+        ),
+        Hash2CurveSuite::Edwards25519Sha512Ro
+        | Hash2CurveSuite::Edwards25519Sha512Nu
+        | Hash2CurveSuite::Curve25519Sha512Ro
+        | Hash2CurveSuite::Curve25519Sha512Nu => (
+            "7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffed",
+            Hash2CurveHash::Sha512,
+            48,
+        ),
+    };
+    let field_len = modulus.len() / 2;
+    let modulus = NonZero::new(U1024::from_be_hex(&format!("{:0>256}", modulus))).unwrap();
+
+    let len = count
+        .checked_mul(l)
+        .ok_or_else(|| Hash2CurveError::InternalError("count is too large".into()))?;
+    let uniform_bytes = expand_message_xmd(hash, input, dst, len)?;
+
+    Ok(uniform_bytes
+        .chunks(l as usize)
+        .map(|tv| {
+            let e = U1024::from_be_slice(&[&[0u8; 128][tv.len()..], tv].concat()).rem(&modulus);
+            e.to_be_bytes()[128 - field_len..].to_vec()
+        })
+        .collect())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -83,22 +602,56 @@ mod tests {
         let msg = b"abcdef0123456789";
         let dst = b"QUUX-V01-CS02-with-P256_XMD:SHA-256_SSWU_RO_";
 
-        // Expected point P (compressed format, 0x02 prefix + x coordinate)
-        // P.x = 65038ac8f2b1def042a5df0b33b1f4eca6bff7cb0f9c6c1526811864e544ed80
-        // P.y = cad44d40a656e7aff4002a8de287abc8ae0482b5ae825822bb870d6df9b56ca3
-        // Since P.y is even (last byte 0xa3 is odd), but we need to check the actual parity
+        // Expected point P
         let expected_x = hex::decode("65038ac8f2b1def042a5df0b33b1f4eca6bff7cb0f9c6c1526811864e544ed80").unwrap();
+        let expected_y = hex::decode("cad44d40a656e7aff4002a8de287abc8ae0482b5ae825822bb870d6df9b56ca3").unwrap();
 
         let result = hash_to_curve_p256_sha256(msg.to_vec(), dst.to_vec()).unwrap();
 
         // Compressed point format: 1 byte prefix (0x02 or 0x03) + 32 bytes x-coordinate
         assert_eq!(result.len(), 33);
 
-        // Check y parity prefix
+        // Check y parity prefix: P.y is odd (last byte 0xa3)
         assert!(result[0] == 0x03);
 
         // Check the x-coordinate matches
         assert_eq!(&result[1..], expected_x.as_slice());
+
+        // Uncompressed point format: 0x04 prefix + x-coordinate + y-coordinate
+        let uncompressed = hash_to_curve_p256_sha256_with_format(
+            msg.to_vec(),
+            dst.to_vec(),
+            PointFormat::Uncompressed,
+        )
+        .unwrap();
+        assert_eq!(
+            uncompressed,
+            FormattedPoint::Sec1 {
+                bytes: [&[0x04][..], &expected_x, &expected_y].concat()
+            }
+        );
+
+        let coordinates = hash_to_curve_p256_sha256_with_format(
+            msg.to_vec(),
+            dst.to_vec(),
+            PointFormat::Coordinates,
+        )
+        .unwrap();
+        assert_eq!(
+            coordinates,
+            FormattedPoint::Coordinates {
+                x: expected_x,
+                y: expected_y
+            }
+        );
+
+        let compressed = hash_to_curve_p256_sha256_with_format(
+            msg.to_vec(),
+            dst.to_vec(),
+            PointFormat::Compressed,
+        )
+        .unwrap();
+        assert_eq!(compressed, FormattedPoint::Sec1 { bytes: result });
     }
 
     #[test]
@@ -130,4 +683,566 @@ mod tests {
             assert_eq!(result_hex, expected_hex, "Failed for message: {:?}", String::from_utf8_lossy(msg));
         }
     }
-}
\ No newline at end of file
+
+    /// Compressed SEC1 encoding of an affine point given as big-endian coordinates.
+    fn sec1_compressed(x: &str, y: &str) -> String {
+        let y = hex::decode(y).unwrap();
+        let prefix = if y[y.len() - 1] & 1 == 1 { "03" } else { "02" };
+        format!("{}{}", prefix, x)
+    }
+
+    /// Compressed Edwards encoding: little-endian y with the sign of x in the top bit.
+    fn edwards_compressed(x: &str, y: &str) -> String {
+        let x = hex::decode(x).unwrap();
+        let mut y = hex::decode(y).unwrap();
+        y.reverse();
+        y[31] |= (x[31] & 1) << 7;
+        hex::encode(y)
+    }
+
+    /// Montgomery encoding: little-endian u.
+    fn montgomery(u: &str) -> String {
+        let mut u = hex::decode(u).unwrap();
+        u.reverse();
+        hex::encode(u)
+    }
+
+    #[test]
+    fn test_hash_to_curve_suites_rfc9380() {
+        // (suite, DST suffix, msg, expected) from RFC 9380 Appendix J.
+        let cases = [
+            (
+                Hash2CurveSuite::P256Sha256Ro,
+                "P256_XMD:SHA-256_SSWU_RO_",
+                &b"abc"[..],
+                sec1_compressed(
+                    "0bb8b87485551aa43ed54f009230450b492fead5f1cc91658775dac4a3388a0f",
+                    "5c41b3d0731a27a7b14bc0bf0ccded2d8751f83493404c84a88e71ffd424212e",
+                ),
+            ),
+            (
+                Hash2CurveSuite::P384Sha384Ro,
+                "P384_XMD:SHA-384_SSWU_RO_",
+                &b""[..],
+                sec1_compressed(
+                    "eb9fe1b4f4e14e7140803c1d99d0a93cd823d2b024040f9c067a8eca1f5a2eeac9ad604973527a356f3fa3aeff0e4d83",
+                    "0c21708cff382b7f4643c07b105c2eaec2cead93a917d825601e63c8f21f6abd9abc22c93c2bed6f235954b25048bb1a",
+                ),
+            ),
+            (
+                Hash2CurveSuite::P384Sha384Ro,
+                "P384_XMD:SHA-384_SSWU_RO_",
+                &b"abc"[..],
+                sec1_compressed(
+                    "e02fc1a5f44a7519419dd314e29863f30df55a514da2d655775a81d413003c4d4e7fd59af0826dfaad4200ac6f60abe1",
+                    "01f638d04d98677d65bef99aef1a12a70a4cbb9270ec55248c04530d8bc1f8f90f8a6a859a7c1f1ddccedf8f96d675f6",
+                ),
+            ),
+            (
+                Hash2CurveSuite::P521Sha512Ro,
+                "P521_XMD:SHA-512_SSWU_RO_",
+                &b""[..],
+                sec1_compressed(
+                    "00fd767cebb2452030358d0e9cf907f525f50920c8f607889a6a35680727f64f4d66b161fafeb2654bea0d35086bec0a10b30b14adef3556ed9f7f1bc23cecc9c088",
+                    "0169ba78d8d851e930680322596e39c78f4fe31b97e57629ef6460ddd68f8763fd7bd767a4e94a80d3d21a3c2ee98347e024fc73ee1c27166dc3fe5eeef782be411d",
+                ),
+            ),
+            (
+                Hash2CurveSuite::P521Sha512Ro,
+                "P521_XMD:SHA-512_SSWU_RO_",
+                &b"abc"[..],
+                sec1_compressed(
+                    "002f89a1677b28054b50d15e1f81ed6669b5a2158211118ebdef8a6efc77f8ccaa528f698214e4340155abc1fa08f8f613ef14a043717503d57e267d57155cf784a4",
+                    "010e0be5dc8e753da8ce51091908b72396d3deed14ae166f66d8ebf0a4e7059ead169ea4bead0232e9b700dd380b316e9361cfdba55a08c73545563a80966ecbb86d",
+                ),
+            ),
+            (
+                Hash2CurveSuite::Secp256k1Sha256Ro,
+                "secp256k1_XMD:SHA-256_SSWU_RO_",
+                &b""[..],
+                sec1_compressed(
+                    "c1cae290e291aee617ebaef1be6d73861479c48b841eaba9b7b5852ddfeb1346",
+                    "64fa678e07ae116126f08b022a94af6de15985c996c3a91b64c406a960e51067",
+                ),
+            ),
+            (
+                Hash2CurveSuite::Secp256k1Sha256Ro,
+                "secp256k1_XMD:SHA-256_SSWU_RO_",
+                &b"abc"[..],
+                sec1_compressed(
+                    "3377e01eab42db296b512293120c6cee72b6ecf9f9205760bd9ff11fb3cb2c4b",
+                    "7f95890f33efebd1044d382a01b1bee0900fb6116f94688d487c6c7b9c8371f6",
+                ),
+            ),
+            (
+                Hash2CurveSuite::P256Sha256Nu,
+                "P256_XMD:SHA-256_SSWU_NU_",
+                &b""[..],
+                sec1_compressed(
+                    "f871caad25ea3b59c16cf87c1894902f7e7b2c822c3d3f73596c5ace8ddd14d1",
+                    "87b9ae23335bee057b99bac1e68588b18b5691af476234b8971bc4f011ddc99b",
+                ),
+            ),
+            (
+                Hash2CurveSuite::P256Sha256Nu,
+                "P256_XMD:SHA-256_SSWU_NU_",
+                &b"abc"[..],
+                sec1_compressed(
+                    "fc3f5d734e8dce41ddac49f47dd2b8a57257522a865c124ed02b92b5237befa4",
+                    "fe4d197ecf5a62645b9690599e1d80e82c500b22ac705a0b421fac7b47157866",
+                ),
+            ),
+            (
+                Hash2CurveSuite::P384Sha384Nu,
+                "P384_XMD:SHA-384_SSWU_NU_",
+                &b""[..],
+                sec1_compressed(
+                    "de5a893c83061b2d7ce6a0d8b049f0326f2ada4b966dc7e72927256b033ef61058029a3bfb13c1c7ececd6641881ae20",
+                    "63f46da6139785674da315c1947e06e9a0867f5608cf24724eb3793a1f5b3809ee28eb21a0c64be3be169afc6cdb38ca",
+                ),
+            ),
+            (
+                Hash2CurveSuite::P521Sha512Nu,
+                "P521_XMD:SHA-512_SSWU_NU_",
+                &b""[..],
+                sec1_compressed(
+                    "01ec604b4e1e3e4c7449b7a41e366e876655538acf51fd40d08b97be066f7d020634e906b1b6942f9174b417027c953d75fb6ec64b8cee2a3672d4f1987d13974705",
+                    "00944fc439b4aad2463e5c9cfa0b0707af3c9a42e37c5a57bb4ecd12fef9fb21508568aedcdd8d2490472df4bbafd79081c81e99f4da3286eddf19be47e9c4cf0e91",
+                ),
+            ),
+            (
+                Hash2CurveSuite::Secp256k1Sha256Nu,
+                "secp256k1_XMD:SHA-256_SSWU_NU_",
+                &b""[..],
+                sec1_compressed(
+                    "a4792346075feae77ac3b30026f99c1441b4ecf666ded19b7522cf65c4c55c5b",
+                    "62c59e2a6aeed1b23be5883e833912b08ba06be7f57c0e9cdc663f31639ff3a7",
+                ),
+            ),
+            (
+                Hash2CurveSuite::Edwards25519Sha512Ro,
+                "edwards25519_XMD:SHA-512_ELL2_RO_",
+                &b""[..],
+                edwards_compressed(
+                    "3c3da6925a3c3c268448dcabb47ccde5439559d9599646a8260e47b1e4822fc6",
+                    "09a6c8561a0b22bef63124c588ce4c62ea83a3c899763af26d795302e115dc21",
+                ),
+            ),
+            (
+                Hash2CurveSuite::Edwards25519Sha512Ro,
+                "edwards25519_XMD:SHA-512_ELL2_RO_",
+                &b"abc"[..],
+                edwards_compressed(
+                    "608040b42285cc0d72cbb3985c6b04c935370c7361f4b7fbdb1ae7f8c1a8ecad",
+                    "1a8395b88338f22e435bbd301183e7f20a5f9de643f11882fb237f88268a5531",
+                ),
+            ),
+            (
+                Hash2CurveSuite::Edwards25519Sha512Nu,
+                "edwards25519_XMD:SHA-512_ELL2_NU_",
+                &b""[..],
+                edwards_compressed(
+                    "1ff2b70ecf862799e11b7ae744e3489aa058ce805dd323a936375a84695e76da",
+                    "222e314d04a4d5725e9f2aff9fb2a6b69ef375a1214eb19021ceab2d687f0f9b",
+                ),
+            ),
+            (
+                Hash2CurveSuite::Edwards25519Sha512Nu,
+                "edwards25519_XMD:SHA-512_ELL2_NU_",
+                &b"abc"[..],
+                edwards_compressed(
+                    "5f13cc69c891d86927eb37bd4afc6672360007c63f68a33ab423a3aa040fd2a8",
+                    "67732d50f9a26f73111dd1ed5dba225614e538599db58ba30aaea1f5c827fa42",
+                ),
+            ),
+            (
+                Hash2CurveSuite::Curve25519Sha512Ro,
+                "curve25519_XMD:SHA-512_ELL2_RO_",
+                &b""[..],
+                montgomery("2de3780abb67e861289f5749d16d3e217ffa722192d16bbd9d1bfb9d112b98c0"),
+            ),
+            (
+                Hash2CurveSuite::Curve25519Sha512Nu,
+                "curve25519_XMD:SHA-512_ELL2_NU_",
+                &b""[..],
+                montgomery("1bb913f0c9daefa0b3375378ffa534bda5526c97391952a7789eb976edfe4d08"),
+            ),
+        ];
+
+        for (suite, suite_id, msg, expected) in cases {
+            if !is_supported(suite) {
+                continue;
+            }
+            let dst = format!("QUUX-V01-CS02-with-{}", suite_id);
+            let result = hash_to_curve(suite, msg.to_vec(), dst.into_bytes()).unwrap();
+            assert_eq!(
+                hex::encode(result),
+                expected,
+                "{:?} {:?}",
+                suite,
+                String::from_utf8_lossy(msg)
+            );
+        }
+    }
+
+    #[test]
+    fn test_hash_to_curve_matches_p256_function() {
+        let dst = b"QUUX-V01-CS02-with-P256_XMD:SHA-256_SSWU_RO_".to_vec();
+        assert_eq!(
+            hash_to_curve(Hash2CurveSuite::P256Sha256Ro, b"abc".to_vec(), dst.clone()).unwrap(),
+            hash_to_curve_p256_sha256(b"abc".to_vec(), dst).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_hash_to_field_rfc9380() {
+        // (suite, DST suffix, count, expected u) for msg "" from RFC 9380 Appendix J.
+        let cases = [
+            (
+                Hash2CurveSuite::P256Sha256Ro,
+                "P256_XMD:SHA-256_SSWU_RO_",
+                vec![
+                    "ad5342c66a6dd0ff080df1da0ea1c04b96e0330dd89406465eeba11582515009",
+                    "8c0f1d43204bd6f6ea70ae8013070a1518b43873bcd850aafa0a9e220e2eea5a",
+                ],
+            ),
+            (
+                Hash2CurveSuite::P256Sha256Nu,
+                "P256_XMD:SHA-256_SSWU_NU_",
+                vec!["b22d487045f80e9edcb0ecc8d4bf77833e2bf1f3a54004d7df1d57f4802d311f"],
+            ),
+            (
+                Hash2CurveSuite::P384Sha384Ro,
+                "P384_XMD:SHA-384_SSWU_RO_",
+                vec![
+                    "25c8d7dc1acd4ee617766693f7f8829396065d1b447eedb155871feffd9c6653279ac7e5c46edb7010a0e4ff64c9f3b4",
+                    "59428be4ed69131df59a0c6a8e188d2d4ece3f1b2a3a02602962b47efa4d7905945b1e2cc80b36aa35c99451073521ac",
+                ],
+            ),
+            (
+                Hash2CurveSuite::P521Sha512Ro,
+                "P521_XMD:SHA-512_SSWU_RO_",
+                vec![
+                    "01e5f09974e5724f25286763f00ce76238c7a6e03dc396600350ee2c4135fb17dc555be99a4a4bae0fd303d4f66d984ed7b6a3ba386093752a855d26d559d69e7e9e",
+                    "00ae593b42ca2ef93ac488e9e09a5fe5a2f6fb330d18913734ff602f2a761fcaaf5f596e790bcc572c9140ec03f6cccc38f767f1c1975a0b4d70b392d95a0c7278aa",
+                ],
+            ),
+            (
+                Hash2CurveSuite::Secp256k1Sha256Ro,
+                "secp256k1_XMD:SHA-256_SSWU_RO_",
+                vec![
+                    "6b0f9910dd2ba71c78f2ee9f04d73b5f4c5f7fc773a701abea1e573cab002fb3",
+                    "1ae6c212e08fe1a5937f6202f929a2cc8ef4ee5b9782db68b0d5799fd8f09e16",
+                ],
+            ),
+            (
+                Hash2CurveSuite::Edwards25519Sha512Ro,
+                "edwards25519_XMD:SHA-512_ELL2_RO_",
+                vec![
+                    "03fef4813c8cb5f98c6eef88fae174e6e7d5380de2b007799ac7ee712d203f3a",
+                    "780bdddd137290c8f589dc687795aafae35f6b674668d92bf92ae793e6a60c75",
+                ],
+            ),
+            (
+                Hash2CurveSuite::Edwards25519Sha512Nu,
+                "edwards25519_XMD:SHA-512_ELL2_NU_",
+                vec!["7f3e7fb9428103ad7f52db32f9df32505d7b427d894c5093f7a0f0374a30641d"],
+            ),
+        ];
+
+        for (suite, suite_id, expected) in cases {
+            let dst = format!("QUUX-V01-CS02-with-{}", suite_id);
+            let u =
+                hash_to_field(suite, Vec::new(), dst.into_bytes(), expected.len() as u32).unwrap();
+            assert_eq!(
+                u.iter().map(hex::encode).collect::<Vec<_>>(),
+                expected,
+                "{:?}",
+                suite
+            );
+        }
+
+        let dst = b"QUUX-V01-CS02-with-P256_XMD:SHA-256_SSWU_RO_".to_vec();
+        assert!(hash_to_field(Hash2CurveSuite::P256Sha256Ro, Vec::new(), dst, 0).is_err());
+    }
+
+    #[test]
+    fn test_expand_message_rfc9380() {
+        // (hash, DST, expected 32-byte output) for msg "" from RFC 9380 Appendix K.
+        // The long DSTs are the prefix padded with `1`s to 256 bytes.
+        let long_dst = |prefix: &str| format!("{:1<256}", format!("{}-long-DST-", prefix));
+        let xmd_cases = [
+            (
+                Hash2CurveHash::Sha256,
+                "QUUX-V01-CS02-with-expander-SHA256-128".to_string(),
+                "68a985b87eb6b46952128911f2a4412bbc302a9d759667f87f7a21d803f07235",
+            ),
+            (
+                Hash2CurveHash::Sha256,
+                long_dst("QUUX-V01-CS02-with-expander-SHA256-128"),
+                "e8dc0c8b686b7ef2074086fbdd2f30e3f8bfbd3bdf177f73f04b97ce618a3ed3",
+            ),
+            (
+                Hash2CurveHash::Sha512,
+                "QUUX-V01-CS02-with-expander-SHA512-256".to_string(),
+                "6b9a7312411d92f921c6f68ca0b6380730a1a4d982c507211a90964c394179ba",
+            ),
+        ];
+        for (hash, dst, expected) in xmd_cases {
+            let out = expand_message_xmd(hash, Vec::new(), dst.into_bytes(), 32).unwrap();
+            assert_eq!(hex::encode(out), expected, "{:?}", hash);
+        }
+
+        let xof_cases = [
+            (
+                Hash2CurveXof::Shake128,
+                "QUUX-V01-CS02-with-expander-SHAKE128".to_string(),
+                "86518c9cd86581486e9485aa74ab35ba150d1c75c88e26b7043e44e2acd735a2",
+            ),
+            (
+                Hash2CurveXof::Shake128,
+                long_dst("QUUX-V01-CS02-with-expander-SHAKE128"),
+                "827c6216330a122352312bccc0c8d6e7a146c5257a776dbd9ad9d75cd880fc53",
+            ),
+            (
+                Hash2CurveXof::Shake256,
+                "QUUX-V01-CS02-with-expander-SHAKE256".to_string(),
+                "2ffc05c48ed32b95d72e807f6eab9f7530dd1c2f013914c8fed38c5ccc15ad76",
+            ),
+            // Appendix K has no long DST vector for SHAKE256; this one was computed by the
+            // rule of Section 5.3.3 (a 64-byte DST hash for k = 256) with Python's hashlib,
+            // which reproduces the SHAKE128 long DST vector above.
+            (
+                Hash2CurveXof::Shake256,
+                long_dst("QUUX-V01-CS02-with-expander-SHAKE256"),
+                "298dc0cf58b9c68810e45a4047f38c1eb562bcc2d31b1d2ea594e0f0ef9a2b7c",
+            ),
+        ];
+        for (xof, dst, expected) in xof_cases {
+            let out = expand_message_xof(xof, Vec::new(), dst.into_bytes(), 32).unwrap();
+            assert_eq!(hex::encode(out), expected, "{:?}", xof);
+        }
+
+        // expand_message_xmd is limited to 255 hash outputs.
+        let dst = b"QUUX-V01-CS02-with-expander-SHA256-128".to_vec();
+        assert!(
+            expand_message_xmd(Hash2CurveHash::Sha256, Vec::new(), dst.clone(), 255 * 32).is_ok()
+        );
+        assert!(expand_message_xmd(Hash2CurveHash::Sha256, Vec::new(), dst, 255 * 32 + 1).is_err());
+    }
+
+    const ALL_SUITES: [Hash2CurveSuite; 12] = [
+        Hash2CurveSuite::P256Sha256Ro,
+        Hash2CurveSuite::P256Sha256Nu,
+        Hash2CurveSuite::P384Sha384Ro,
+        Hash2CurveSuite::P384Sha384Nu,
+        Hash2CurveSuite::P521Sha512Ro,
+        Hash2CurveSuite::P521Sha512Nu,
+        Hash2CurveSuite::Secp256k1Sha256Ro,
+        Hash2CurveSuite::Secp256k1Sha256Nu,
+        Hash2CurveSuite::Edwards25519Sha512Ro,
+        Hash2CurveSuite::Edwards25519Sha512Nu,
+        Hash2CurveSuite::Curve25519Sha512Ro,
+        Hash2CurveSuite::Curve25519Sha512Nu,
+    ];
+
+    /// Whether the suite is built in, which for the 25519 suites needs `hash2curve-25519`.
+    fn is_supported(suite: Hash2CurveSuite) -> bool {
+        cfg!(feature = "hash2curve-25519")
+            || !matches!(
+                suite,
+                Hash2CurveSuite::Edwards25519Sha512Ro
+                    | Hash2CurveSuite::Edwards25519Sha512Nu
+                    | Hash2CurveSuite::Curve25519Sha512Ro
+                    | Hash2CurveSuite::Curve25519Sha512Nu
+            )
+    }
+
+    #[test]
+    fn test_unsupported_suites_are_reported() {
+        for suite in ALL_SUITES {
+            let result = hash_to_curve(suite, b"abc".to_vec(), b"dst".to_vec());
+            if is_supported(suite) {
+                assert!(result.is_ok(), "{:?}", suite);
+            } else {
+                assert!(matches!(result, Err(Hash2CurveError::UnsupportedSuite(_))));
+            }
+        }
+    }
+
+    #[test]
+    fn test_empty_dst_rejected() {
+        let input = b"abc".to_vec();
+        for suite in ALL_SUITES {
+            assert!(matches!(
+                hash_to_curve(suite, input.clone(), Vec::new()),
+                Err(Hash2CurveError::EmptyDst)
+            ));
+            assert!(matches!(
+                hash_to_field(suite, input.clone(), Vec::new(), 2),
+                Err(Hash2CurveError::EmptyDst)
+            ));
+        }
+        assert!(matches!(
+            hash_to_curve_p256_sha256(input.clone(), Vec::new()),
+            Err(Hash2CurveError::EmptyDst)
+        ));
+        assert!(matches!(
+            hash_to_curve_p256_sha256_with_format(
+                input.clone(),
+                Vec::new(),
+                PointFormat::Compressed
+            ),
+            Err(Hash2CurveError::EmptyDst)
+        ));
+        assert!(matches!(
+            hash_to_curve_ristretto255_sha512(input.clone(), Vec::new()),
+            Err(Hash2CurveError::EmptyDst)
+        ));
+        assert!(matches!(
+            expand_message_xmd(Hash2CurveHash::Sha256, input.clone(), Vec::new(), 32),
+            Err(Hash2CurveError::EmptyDst)
+        ));
+        assert!(matches!(
+            expand_message_xof(Hash2CurveXof::Shake128, input, Vec::new(), 32),
+            Err(Hash2CurveError::EmptyDst)
+        ));
+    }
+
+    #[test]
+    fn test_dst_boundary_lengths() {
+        use sha2::Digest;
+
+        let input = b"abc".to_vec();
+        for suite in ALL_SUITES.into_iter().filter(|suite| is_supported(*suite)) {
+            for len in [1, 255, 256, 1000] {
+                assert!(
+                    hash_to_curve(suite, input.clone(), vec![b'd'; len]).is_ok(),
+                    "{:?} with a {} byte DST",
+                    suite,
+                    len
+                );
+            }
+
+            // A DST longer than 255 bytes is replaced by H("H2C-OVERSIZE-DST-" || DST).
+            let long_dst = vec![b'd'; 256];
+            let salted = [OVERSIZE_DST_SALT, &long_dst].concat();
+            let hashed_dst = match suite {
+                Hash2CurveSuite::P256Sha256Ro
+                | Hash2CurveSuite::P256Sha256Nu
+                | Hash2CurveSuite::Secp256k1Sha256Ro
+                | Hash2CurveSuite::Secp256k1Sha256Nu => sha2::Sha256::digest(&salted).to_vec(),
+                Hash2CurveSuite::P384Sha384Ro | Hash2CurveSuite::P384Sha384Nu => {
+                    sha2::Sha384::digest(&salted).to_vec()
+                }
+                _ => sha2::Sha512::digest(&salted).to_vec(),
+            };
+            assert_eq!(
+                hash_to_curve(suite, input.clone(), long_dst).unwrap(),
+                hash_to_curve(suite, input.clone(), hashed_dst).unwrap(),
+                "{:?}",
+                suite
+            );
+        }
+
+        let long_dst = vec![b'd'; 256];
+        assert_eq!(
+            hash_to_curve_ristretto255_sha512(input.clone(), long_dst.clone()).unwrap(),
+            hash_to_curve_ristretto255_sha512(
+                input.clone(),
+                sha2::Sha512::digest([OVERSIZE_DST_SALT, &long_dst].concat()).to_vec()
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_hash_to_curve_parts_are_length_prefixed() {
+        let input_parts = vec![b"abcdef".to_vec(), Vec::new(), b"0123456789".to_vec()];
+        let encoded_input = [
+            &b"\x00\x06abcdef"[..],
+            &b"\x00\x00"[..],
+            &b"\x00\x0a0123456789"[..],
+        ]
+        .concat();
+        let dst_parts = vec![
+            b"QUUX-V01-CS02-with-".to_vec(),
+            b"P256_XMD:SHA-256_SSWU_RO_".to_vec(),
+        ];
+        assert_eq!(
+            hash_to_curve_parts(
+                Hash2CurveSuite::P256Sha256Ro,
+                input_parts.clone(),
+                dst_parts.clone()
+            )
+            .unwrap(),
+            hash_to_curve_p256_sha256(encoded_input.clone(), dst_parts.concat()).unwrap()
+        );
+
+        // Long DSTs are measured and hashed across their parts.
+        let long_dst_parts = vec![vec![b'd'; 200], vec![b'e'; 200]];
+        for suite in ALL_SUITES.into_iter().filter(|suite| is_supported(*suite)) {
+            for dst_parts in [
+                vec![b"dst-".to_vec(), b"parts".to_vec()],
+                long_dst_parts.clone(),
+            ] {
+                assert_eq!(
+                    hash_to_curve_parts(suite, input_parts.clone(), dst_parts.clone()).unwrap(),
+                    hash_to_curve(suite, encoded_input.clone(), dst_parts.concat()).unwrap(),
+                    "{:?}",
+                    suite
+                );
+            }
+        }
+
+        let dst_parts = vec![b"test_ristretto255_".to_vec(), b"hash_to_curve".to_vec()];
+        assert_eq!(
+            hash_to_curve_ristretto255_sha512_parts(input_parts, dst_parts.clone()).unwrap(),
+            hash_to_curve_ristretto255_sha512(encoded_input, dst_parts.concat()).unwrap()
+        );
+
+        // Moving bytes between parts changes the point.
+        let split = |parts: [&[u8]; 2]| parts.iter().map(|part| part.to_vec()).collect();
+        assert_ne!(
+            hash_to_curve_parts(
+                Hash2CurveSuite::P256Sha256Ro,
+                split([b"ab", b"c"]),
+                dst_parts.clone()
+            )
+            .unwrap(),
+            hash_to_curve_parts(
+                Hash2CurveSuite::P256Sha256Ro,
+                split([b"a", b"bc"]),
+                dst_parts.clone()
+            )
+            .unwrap()
+        );
+        assert_ne!(
+            hash_to_curve_ristretto255_sha512_parts(split([b"ab", b"c"]), dst_parts.clone())
+                .unwrap(),
+            hash_to_curve_ristretto255_sha512_parts(split([b"a", b"bc"]), dst_parts).unwrap()
+        );
+        assert!(matches!(
+            hash_to_curve_parts(
+                Hash2CurveSuite::P256Sha256Ro,
+                vec![vec![0; 65536]],
+                vec![b"dst".to_vec()]
+            ),
+            Err(Hash2CurveError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_hash_to_curve_parts_empty_dst_rejected() {
+        for dst_parts in [Vec::new(), vec![Vec::new(), Vec::new()]] {
+            assert!(matches!(
+                hash_to_curve_parts(Hash2CurveSuite::P256Sha256Ro, Vec::new(), dst_parts.clone()),
+                Err(Hash2CurveError::EmptyDst)
+            ));
+            assert!(matches!(
+                hash_to_curve_ristretto255_sha512_parts(Vec::new(), dst_parts),
+                Err(Hash2CurveError::EmptyDst)
+            ));
+        }
+    }
+}
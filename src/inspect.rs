@@ -0,0 +1,474 @@
+// SPDX-FileCopyrightText: 2026 Digg - Agency for Digital Government
+//
+// SPDX-License-Identifier: EUPL-1.2
+
+//! This module decodes serialized OPAQUE artifacts into their component fields,
+//! for debugging and inspection.
+
+use crate::common::{DefaultCipherSuite, DEFAULT_CIPHER_SUITE_ID};
+use opaque_ke::{
+    CredentialFinalization, CredentialRequest, CredentialResponse, RegistrationRequest,
+    RegistrationResponse, RegistrationUpload, ServerRegistration, ServerSetup,
+};
+
+// Field lengths of `DefaultCipherSuite`, named as in RFC 9807.
+/// Length of a serialized OPRF group element.
+pub(crate) const NOE: usize = 33;
+/// Length of a serialized key exchange public key.
+pub(crate) const NPK: usize = 33;
+/// Length of a serialized key exchange private key.
+pub(crate) const NSK: usize = 32;
+/// Length of a nonce.
+pub(crate) const NN: usize = 32;
+/// Length of the OPRF hash output.
+pub(crate) const NH: usize = 32;
+/// Length of a MAC.
+pub(crate) const NM: usize = 32;
+
+/// Fields whose values `describe_message` withholds, reporting only their offset and length.
+const SECRET_FIELDS: &[&str] = &[
+    "oprf_seed",
+    "server_private_key",
+    "masking_key",
+    "envelope_auth_tag",
+];
+
+#[derive(Debug, thiserror::Error, uniffi::Error)]
+pub enum InspectError {
+    #[error("Inspection failed: {0}")]
+    Generic(String),
+    #[error("Expected {expected} bytes, got {actual}")]
+    InvalidLength { expected: u32, actual: u32 },
+    #[error("Invalid password file: {0}")]
+    InvalidPasswordFile(String),
+}
+
+/// The kinds of serialized artifact that can be described.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum MessageKind {
+    ServerSetup,
+    RegistrationRequest,
+    RegistrationResponse,
+    RegistrationUpload,
+    PasswordFile,
+    CredentialRequest,
+    CredentialResponse,
+    CredentialFinalization,
+}
+
+#[derive(uniffi::Record)]
+pub struct MessageField {
+    pub name: String,
+    pub offset: u32,
+    pub length: u32,
+    /// The bytes of the field, or `None` for a secret field.
+    pub value: Option<Vec<u8>>,
+}
+
+#[derive(uniffi::Record)]
+pub struct MessageDescription {
+    pub kind: MessageKind,
+    pub suite: String,
+    pub length: u32,
+    pub fields: Vec<MessageField>,
+}
+
+/// The components of a password file produced by `server_registration_finish`.
+#[derive(Debug, uniffi::Record)]
+pub struct PasswordFileInfo {
+    pub suite: String,
+    pub length: u32,
+    pub client_public_key: Vec<u8>,
+    /// The length of the masking key, which is not returned.
+    pub masking_key_length: u32,
+    pub envelope_nonce: Vec<u8>,
+    pub envelope_auth_tag_length: u32,
+}
+
+impl MessageKind {
+    /// The names and lengths of the fields of this kind, in serialization order.
+    fn layout(self) -> &'static [(&'static str, usize)] {
+        match self {
+            MessageKind::ServerSetup => &[
+                ("oprf_seed", NH),
+                ("server_private_key", NSK),
+                ("dummy_public_key", NPK),
+            ],
+            MessageKind::RegistrationRequest => &[("blinded_message", NOE)],
+            MessageKind::RegistrationResponse => {
+                &[("evaluated_message", NOE), ("server_public_key", NPK)]
+            }
+            MessageKind::RegistrationUpload | MessageKind::PasswordFile => &[
+                ("client_public_key", NPK),
+                ("masking_key", NH),
+                ("envelope_nonce", NN),
+                ("envelope_auth_tag", NM),
+            ],
+            MessageKind::CredentialRequest => &[
+                ("blinded_message", NOE),
+                ("client_nonce", NN),
+                ("client_public_keyshare", NPK),
+            ],
+            MessageKind::CredentialResponse => &[
+                ("evaluated_message", NOE),
+                ("masking_nonce", NN),
+                ("masked_response", NPK + NN + NM),
+                ("server_nonce", NN),
+                ("server_public_keyshare", NPK),
+                ("server_mac", NM),
+            ],
+            MessageKind::CredentialFinalization => &[("client_mac", NM)],
+        }
+    }
+
+    /// Deserializes `message` as this kind, to check that its contents are valid.
+    fn validate(self, message: &[u8]) -> Result<(), InspectError> {
+        fn err<E: std::fmt::Debug>(e: E) -> InspectError {
+            InspectError::Generic(format!("{:?}", e))
+        }
+
+        match self {
+            MessageKind::ServerSetup => ServerSetup::<DefaultCipherSuite>::deserialize(message)
+                .map(|_| ())
+                .map_err(err),
+            MessageKind::RegistrationRequest => {
+                RegistrationRequest::<DefaultCipherSuite>::deserialize(message)
+                    .map(|_| ())
+                    .map_err(err)
+            }
+            MessageKind::RegistrationResponse => {
+                RegistrationResponse::<DefaultCipherSuite>::deserialize(message)
+                    .map(|_| ())
+                    .map_err(err)
+            }
+            MessageKind::RegistrationUpload => {
+                RegistrationUpload::<DefaultCipherSuite>::deserialize(message)
+                    .map(|_| ())
+                    .map_err(err)
+            }
+            MessageKind::PasswordFile => {
+                ServerRegistration::<DefaultCipherSuite>::deserialize(message)
+                    .map(|_| ())
+                    .map_err(err)
+            }
+            MessageKind::CredentialRequest => {
+                CredentialRequest::<DefaultCipherSuite>::deserialize(message)
+                    .map(|_| ())
+                    .map_err(err)
+            }
+            MessageKind::CredentialResponse => {
+                CredentialResponse::<DefaultCipherSuite>::deserialize(message)
+                    .map(|_| ())
+                    .map_err(err)
+            }
+            MessageKind::CredentialFinalization => {
+                CredentialFinalization::<DefaultCipherSuite>::deserialize(message)
+                    .map(|_| ())
+                    .map_err(err)
+            }
+        }
+    }
+}
+
+/// A field of a message, borrowed from it.
+struct Field<'a> {
+    name: &'static str,
+    offset: usize,
+    value: &'a [u8],
+}
+
+/// Checks that `message` is a well-formed `kind` for `DefaultCipherSuite` and splits it
+/// into its fields.
+fn split_message(kind: MessageKind, message: &[u8]) -> Result<Vec<Field<'_>>, InspectError> {
+    let layout = kind.layout();
+    let expected: usize = layout.iter().map(|(_, len)| len).sum();
+    if message.len() != expected {
+        return Err(InspectError::InvalidLength {
+            expected: expected as u32,
+            actual: message.len() as u32,
+        });
+    }
+
+    kind.validate(message)?;
+
+    let mut offset = 0;
+    Ok(layout
+        .iter()
+        .map(|(name, len)| {
+            let field = Field {
+                name,
+                offset,
+                value: &message[offset..offset + len],
+            };
+            offset += len;
+            field
+        })
+        .collect())
+}
+
+/// Decodes a serialized protocol message, server setup or password file into its fields.
+///
+/// The message is checked to be well-formed for `DefaultCipherSuite` before it is split up.
+/// The OPRF seed, server private key, masking key and envelope authentication tag are
+/// secret, so only their offset and length are returned.
+#[uniffi::export]
+pub fn describe_message(
+    kind: MessageKind,
+    message: Vec<u8>,
+) -> Result<MessageDescription, InspectError> {
+    let fields = split_message(kind, &message)?
+        .into_iter()
+        .map(|field| MessageField {
+            name: field.name.to_string(),
+            offset: field.offset as u32,
+            length: field.value.len() as u32,
+            value: (!SECRET_FIELDS.contains(&field.name)).then(|| field.value.to_vec()),
+        })
+        .collect();
+
+    Ok(MessageDescription {
+        kind,
+        suite: DEFAULT_CIPHER_SUITE_ID.to_string(),
+        length: message.len() as u32,
+        fields,
+    })
+}
+
+/// Decodes a password file into its components, without the masking key and envelope
+/// authentication tag, which are secret.
+#[uniffi::export]
+pub fn inspect_password_file(password_file: Vec<u8>) -> Result<PasswordFileInfo, InspectError> {
+    let fields = split_message(MessageKind::PasswordFile, &password_file)?;
+    let field = |name: &str| {
+        fields
+            .iter()
+            .find(|field| field.name == name)
+            .map(|field| field.value)
+            .ok_or_else(|| InspectError::Generic(format!("{} is missing", name)))
+    };
+
+    Ok(PasswordFileInfo {
+        suite: DEFAULT_CIPHER_SUITE_ID.to_string(),
+        length: password_file.len() as u32,
+        client_public_key: field("client_public_key")?.to_vec(),
+        masking_key_length: field("masking_key")?.len() as u32,
+        envelope_nonce: field("envelope_nonce")?.to_vec(),
+        envelope_auth_tag_length: field("envelope_auth_tag")?.len() as u32,
+    })
+}
+
+/// Checks that a password file is well-formed for `DefaultCipherSuite`, e.g. in a database
+/// integrity check.
+///
+/// Besides its length and client public key, which `describe_message` checks, the masking
+/// key, envelope nonce and envelope authentication tag must not be all zeros, which they
+/// only are in a zeroed or truncated-and-padded record.
+#[uniffi::export]
+pub fn validate_password_file(password_file: Vec<u8>) -> Result<(), InspectError> {
+    let fields = split_message(MessageKind::PasswordFile, &password_file)?;
+    for field in &fields[1..] {
+        if field.value.iter().all(|&b| b == 0) {
+            return Err(InspectError::InvalidPasswordFile(format!(
+                "{} is all zeros",
+                field.name
+            )));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        client_login::{client_login_finish, client_login_start},
+        client_registration::{client_registration_finish, client_registration_start},
+        common::IdentityMode,
+        server::{server_public_key, server_setup},
+        server_login::server_login_start,
+        server_registration::{server_registration_finish, server_registration_start},
+    };
+
+    #[test]
+    fn describes_every_message_of_a_full_flow() {
+        let password = b"password".to_vec();
+        let client_id = b"client".to_vec();
+        let identity_mode = IdentityMode::Explicit {
+            client: client_id.clone(),
+            server: b"server".to_vec(),
+        };
+        let setup = server_setup();
+
+        let reg_start = client_registration_start(password.clone()).unwrap();
+        let registration_response = server_registration_start(
+            setup.clone(),
+            reg_start.registration_request.clone(),
+            &client_id,
+        )
+        .unwrap();
+        let reg_finish = client_registration_finish(
+            password.clone(),
+            reg_start.client_registration,
+            registration_response.clone(),
+            identity_mode.clone(),
+        )
+        .unwrap();
+        let password_file =
+            server_registration_finish(reg_finish.registration_upload.clone()).unwrap();
+
+        let login_start = client_login_start(password.clone()).unwrap();
+        let server_start = server_login_start(
+            setup.clone(),
+            password_file.clone(),
+            login_start.credential_request.clone(),
+            &client_id,
+            None,
+            identity_mode.clone(),
+        )
+        .unwrap();
+        let login_finish = client_login_finish(
+            server_start.credential_response.clone(),
+            login_start.client_registration,
+            password,
+            Vec::new(),
+            identity_mode,
+        )
+        .unwrap();
+
+        let messages = [
+            (MessageKind::ServerSetup, setup.clone()),
+            (
+                MessageKind::RegistrationRequest,
+                reg_start.registration_request,
+            ),
+            (
+                MessageKind::RegistrationResponse,
+                registration_response.clone(),
+            ),
+            (
+                MessageKind::RegistrationUpload,
+                reg_finish.registration_upload,
+            ),
+            (MessageKind::PasswordFile, password_file),
+            (
+                MessageKind::CredentialRequest,
+                login_start.credential_request,
+            ),
+            (
+                MessageKind::CredentialResponse,
+                server_start.credential_response,
+            ),
+            (
+                MessageKind::CredentialFinalization,
+                login_finish.credential_finalization,
+            ),
+        ];
+
+        for (kind, message) in messages {
+            let description = describe_message(kind, message.clone())
+                .unwrap_or_else(|e| panic!("{:?}: {}", kind, e));
+            let mut offset = 0;
+            for field in &description.fields {
+                assert_eq!(field.offset as usize, offset, "{:?}", kind);
+                let end = offset + field.length as usize;
+                if let Some(value) = &field.value {
+                    assert_eq!(value[..], message[offset..end], "{:?}", kind);
+                }
+                // Secret fields, e.g. the masking key of a registration upload, are withheld
+                assert_eq!(
+                    field.value.is_none(),
+                    SECRET_FIELDS.contains(&field.name.as_str()),
+                    "{:?} {}",
+                    kind,
+                    field.name
+                );
+                offset = end;
+            }
+            assert_eq!(offset, message.len(), "{:?}", kind);
+        }
+
+        let response =
+            describe_message(MessageKind::RegistrationResponse, registration_response).unwrap();
+        assert_eq!(
+            response.fields[1].value,
+            Some(server_public_key(setup.clone()).unwrap())
+        );
+        let setup = describe_message(MessageKind::ServerSetup, setup).unwrap();
+        assert!(setup.fields[0].value.is_none() && setup.fields[1].value.is_none());
+    }
+
+    fn password_file() -> Vec<u8> {
+        let password = b"password".to_vec();
+        let reg_start = client_registration_start(password.clone()).unwrap();
+        let registration_response =
+            server_registration_start(server_setup(), reg_start.registration_request, b"alice")
+                .unwrap();
+        let reg_finish = client_registration_finish(
+            password,
+            reg_start.client_registration,
+            registration_response,
+            IdentityMode::DefaultPublicKeys,
+        )
+        .unwrap();
+        server_registration_finish(reg_finish.registration_upload).unwrap()
+    }
+
+    #[test]
+    fn inspects_password_file() {
+        let password_file = password_file();
+        let info = inspect_password_file(password_file.clone()).unwrap();
+        assert_eq!(info.suite, DEFAULT_CIPHER_SUITE_ID);
+        assert_eq!(info.length, 129);
+        assert_eq!(info.client_public_key, password_file[..NPK]);
+        assert_eq!(info.masking_key_length, NH as u32);
+        assert_eq!(info.envelope_nonce, password_file[NPK + NH..NPK + NH + NN]);
+        assert_eq!(info.envelope_auth_tag_length, NM as u32);
+        validate_password_file(password_file).unwrap();
+    }
+
+    #[test]
+    fn invalid_password_files_are_err() {
+        let password_file = password_file();
+        assert!(matches!(
+            validate_password_file(Vec::new()),
+            Err(InspectError::InvalidLength { .. })
+        ));
+        assert!(matches!(
+            validate_password_file(password_file[..128].to_vec()),
+            Err(InspectError::InvalidLength { .. })
+        ));
+
+        let mut bad_key = password_file.clone();
+        bad_key[1..NPK].fill(0xff);
+        assert!(matches!(
+            validate_password_file(bad_key),
+            Err(InspectError::Generic(_))
+        ));
+
+        let mut zero_nonce = password_file;
+        zero_nonce[NPK + NH..NPK + NH + NN].fill(0);
+        assert!(matches!(
+            validate_password_file(zero_nonce),
+            Err(InspectError::InvalidPasswordFile(_))
+        ));
+    }
+
+    #[test]
+    fn wrong_length_is_err() {
+        assert!(matches!(
+            describe_message(MessageKind::PasswordFile, vec![0u8; 12]),
+            Err(InspectError::InvalidLength {
+                expected: 129,
+                actual: 12
+            })
+        ));
+    }
+
+    #[test]
+    fn invalid_point_is_err() {
+        assert!(matches!(
+            describe_message(MessageKind::RegistrationRequest, vec![0xffu8; NOE]),
+            Err(InspectError::Generic(_))
+        ));
+    }
+}
@@ -2,13 +2,29 @@
 //
 // SPDX-License-Identifier: EUPL-1.2
 
-mod client_login;
+pub mod channel_binding;
+pub mod client_driver;
+pub mod client_login;
 pub mod client_registration;
 pub mod common;
+pub mod credential_identifier;
+pub mod device_enrolment;
+pub mod framing;
+pub mod group;
 pub mod hash2curve;
+pub mod inspect;
+pub mod login_context;
+pub mod oprf;
+pub mod password;
+pub mod privacy_pass;
+pub mod recovery;
 pub mod server;
-mod server_login;
+pub mod server_login;
 pub mod server_registration;
+pub mod signing;
+pub mod threshold_oprf;
+#[cfg(feature = "sqlite")]
+pub mod sqlite_store;
 
 uniffi::setup_scaffolding!();
 
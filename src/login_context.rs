@@ -0,0 +1,265 @@
+// SPDX-FileCopyrightText: 2026 Digg - Agency for Digital Government
+//
+// SPDX-License-Identifier: EUPL-1.2
+
+//! This module builds the `context` passed to `server_login_start`, `client_login_finish`
+//! and `server_login_finish` from typed fields, so the client and the server encode it the
+//! same way.
+//!
+//! A context that differs between the two sides only shows up as an opaque login error, so
+//! [`diff_login_contexts`] decodes two encoded contexts and reports the fields that differ.
+
+use crate::common::push_length_prefixed;
+
+/// Prefix of every encoded login context, identifying its format.
+const LOGIN_CONTEXT_TAG: &[u8] = b"OPAQUE-Login-Context-v1";
+
+#[derive(Debug, thiserror::Error, uniffi::Error)]
+pub enum LoginContextError {
+    #[error("Invalid parameter: {0}")]
+    InvalidParameter(String),
+    #[error("Malformed login context: {0}")]
+    Malformed(String),
+}
+
+/// The fields a login is bound to.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct LoginContext {
+    /// The application, e.g. its bundle or package ID.
+    pub app_id: String,
+    /// The version of the application protocol around the login.
+    pub protocol_version: u32,
+    /// The device the login is made from.
+    pub device_id: String,
+    /// What the login is for, e.g. `login` or `step-up`.
+    pub purpose: String,
+    /// A context from `channel_binding_context`, binding the login to a TLS session.
+    pub channel_binding: Option<Vec<u8>>,
+}
+
+/// A field of a [`LoginContext`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum LoginContextField {
+    AppId,
+    ProtocolVersion,
+    DeviceId,
+    Purpose,
+    ChannelBinding,
+}
+
+/// Reads fields from an encoded login context, failing on truncated input.
+struct Reader<'a> {
+    input: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize, name: &str) -> Result<&'a [u8], LoginContextError> {
+        if self.input.len() < len {
+            return Err(LoginContextError::Malformed(format!(
+                "{} is truncated",
+                name
+            )));
+        }
+        let (value, rest) = self.input.split_at(len);
+        self.input = rest;
+        Ok(value)
+    }
+
+    fn take_length_prefixed(&mut self, name: &str) -> Result<&'a [u8], LoginContextError> {
+        let len = u16::from_be_bytes(self.take(2, name)?.try_into().unwrap());
+        self.take(len as usize, name)
+    }
+
+    fn take_string(&mut self, name: &str) -> Result<String, LoginContextError> {
+        String::from_utf8(self.take_length_prefixed(name)?.to_vec())
+            .map_err(|_| LoginContextError::Malformed(format!("{} is not UTF-8", name)))
+    }
+}
+
+/// Encodes a login context.
+///
+/// The encoding is a format tag followed by the fields in declaration order: the strings
+/// and the channel binding with a 2-byte big-endian length prefix, the protocol version as
+/// 4 big-endian bytes, and a byte of 0 or 1 before the channel binding for its absence or
+/// presence. Equal contexts always encode to equal bytes and distinct ones never do.
+#[uniffi::export]
+pub fn encode_login_context(context: LoginContext) -> Result<Vec<u8>, LoginContextError> {
+    let mut out = LOGIN_CONTEXT_TAG.to_vec();
+    push_length_prefixed(&mut out, "the app ID", context.app_id.as_bytes())
+        .map_err(LoginContextError::InvalidParameter)?;
+    out.extend_from_slice(&context.protocol_version.to_be_bytes());
+    push_length_prefixed(&mut out, "the device ID", context.device_id.as_bytes())
+        .map_err(LoginContextError::InvalidParameter)?;
+    push_length_prefixed(&mut out, "the purpose", context.purpose.as_bytes())
+        .map_err(LoginContextError::InvalidParameter)?;
+    match &context.channel_binding {
+        Some(channel_binding) => {
+            out.push(1);
+            push_length_prefixed(&mut out, "the channel binding", channel_binding)
+                .map_err(LoginContextError::InvalidParameter)?;
+        }
+        None => out.push(0),
+    }
+
+    // OPAQUE limits the context to a 2-byte length.
+    if out.len() > u16::MAX as usize {
+        return Err(LoginContextError::InvalidParameter(
+            "the encoded context is longer than 65535 bytes".into(),
+        ));
+    }
+    Ok(out)
+}
+
+/// Decodes a login context encoded with [`encode_login_context`].
+#[uniffi::export]
+pub fn decode_login_context(encoded: Vec<u8>) -> Result<LoginContext, LoginContextError> {
+    let mut reader = Reader { input: &encoded };
+    if reader.take(LOGIN_CONTEXT_TAG.len(), "the format tag")? != LOGIN_CONTEXT_TAG {
+        return Err(LoginContextError::Malformed(
+            "not an encoded login context".into(),
+        ));
+    }
+    let app_id = reader.take_string("the app ID")?;
+    let protocol_version =
+        u32::from_be_bytes(reader.take(4, "the protocol version")?.try_into().unwrap());
+    let device_id = reader.take_string("the device ID")?;
+    let purpose = reader.take_string("the purpose")?;
+    let channel_binding = match reader.take(1, "the channel binding")? {
+        [0] => None,
+        [1] => Some(reader.take_length_prefixed("the channel binding")?.to_vec()),
+        _ => {
+            return Err(LoginContextError::Malformed(
+                "the channel binding flag is not 0 or 1".into(),
+            ))
+        }
+    };
+    if !reader.input.is_empty() {
+        return Err(LoginContextError::Malformed(
+            "trailing bytes after the login context".into(),
+        ));
+    }
+
+    Ok(LoginContext {
+        app_id,
+        protocol_version,
+        device_id,
+        purpose,
+        channel_binding,
+    })
+}
+
+/// Decodes two encoded login contexts, e.g. the client's and the server's of a failed
+/// login, and returns the fields that differ between them, empty if they are equal.
+#[uniffi::export]
+pub fn diff_login_contexts(
+    a: Vec<u8>,
+    b: Vec<u8>,
+) -> Result<Vec<LoginContextField>, LoginContextError> {
+    let a = decode_login_context(a)?;
+    let b = decode_login_context(b)?;
+    let mut fields = Vec::new();
+    if a.app_id != b.app_id {
+        fields.push(LoginContextField::AppId);
+    }
+    if a.protocol_version != b.protocol_version {
+        fields.push(LoginContextField::ProtocolVersion);
+    }
+    if a.device_id != b.device_id {
+        fields.push(LoginContextField::DeviceId);
+    }
+    if a.purpose != b.purpose {
+        fields.push(LoginContextField::Purpose);
+    }
+    if a.channel_binding != b.channel_binding {
+        fields.push(LoginContextField::ChannelBinding);
+    }
+    Ok(fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context() -> LoginContext {
+        LoginContext {
+            app_id: "se.digg.app".into(),
+            protocol_version: 2,
+            device_id: "device".into(),
+            purpose: "login".into(),
+            channel_binding: None,
+        }
+    }
+
+    #[test]
+    fn test_encoding() {
+        let mut expected = LOGIN_CONTEXT_TAG.to_vec();
+        expected.extend_from_slice(b"\x00\x0bse.digg.app");
+        expected.extend_from_slice(&[0, 0, 0, 2]);
+        expected.extend_from_slice(b"\x00\x06device");
+        expected.extend_from_slice(b"\x00\x05login");
+        expected.push(0);
+        assert_eq!(encode_login_context(context()).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for channel_binding in [None, Some(vec![]), Some(vec![1, 2, 3])] {
+            let context = LoginContext {
+                channel_binding,
+                ..context()
+            };
+            let encoded = encode_login_context(context.clone()).unwrap();
+            assert_eq!(decode_login_context(encoded).unwrap(), context);
+        }
+    }
+
+    #[test]
+    fn test_malformed_encodings_are_rejected() {
+        let encoded = encode_login_context(context()).unwrap();
+        for len in 0..encoded.len() {
+            assert!(decode_login_context(encoded[..len].to_vec()).is_err());
+        }
+        let mut trailing = encoded.clone();
+        trailing.push(0);
+        assert!(decode_login_context(trailing).is_err());
+        let mut bad_flag = encoded;
+        *bad_flag.last_mut().unwrap() = 2;
+        assert!(decode_login_context(bad_flag).is_err());
+        assert!(decode_login_context(b"context".to_vec()).is_err());
+    }
+
+    #[test]
+    fn test_diff_reports_differing_fields() {
+        let encoded = encode_login_context(context()).unwrap();
+        assert!(diff_login_contexts(encoded.clone(), encoded.clone())
+            .unwrap()
+            .is_empty());
+
+        let other = LoginContext {
+            protocol_version: 3,
+            purpose: "step-up".into(),
+            channel_binding: Some(vec![1]),
+            ..context()
+        };
+        assert_eq!(
+            diff_login_contexts(encoded, encode_login_context(other).unwrap()).unwrap(),
+            vec![
+                LoginContextField::ProtocolVersion,
+                LoginContextField::Purpose,
+                LoginContextField::ChannelBinding
+            ]
+        );
+    }
+
+    #[test]
+    fn test_overlong_fields_are_rejected() {
+        let context = LoginContext {
+            device_id: "d".repeat(65536),
+            ..context()
+        };
+        assert!(matches!(
+            encode_login_context(context),
+            Err(LoginContextError::InvalidParameter(_))
+        ));
+    }
+}
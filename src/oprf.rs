@@ -0,0 +1,894 @@
+// SPDX-FileCopyrightText: 2026 Digg - Agency for Digital Government
+//
+// SPDX-License-Identifier: EUPL-1.2
+
+//! This module exports the OPRF, VOPRF and POPRF protocols of RFC 9497 as standalone
+//! client blind/finalize and server evaluate functions.
+//!
+//! All keys, client states and messages are passed around in their RFC 9497 serialization,
+//! so the client and server sides can run in different processes or on different devices.
+
+use rand::rngs::OsRng;
+use voprf::{
+    BlindedElement, EvaluationElement, Group, OprfClient, OprfServer, PoprfClient, PoprfServer,
+    Proof, VoprfClient, VoprfServer,
+};
+
+#[derive(Debug, thiserror::Error, uniffi::Error)]
+pub enum OprfError {
+    #[error("OPRF failed: {0}")]
+    Generic(String),
+    #[error("Proof verification failed")]
+    ProofVerification,
+    #[error("Invalid parameter: {0}")]
+    InvalidParameter(String),
+}
+
+impl From<voprf::Error> for OprfError {
+    fn from(e: voprf::Error) -> Self {
+        match e {
+            voprf::Error::ProofVerification => OprfError::ProofVerification,
+            e => OprfError::Generic(format!("{:?}", e)),
+        }
+    }
+}
+
+/// The RFC 9497 ciphersuites that can be used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum OprfSuite {
+    /// `P256-SHA256`
+    P256Sha256,
+    /// `ristretto255-SHA512`
+    Ristretto255Sha512,
+}
+
+/// The RFC 9497 protocol variants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum OprfMode {
+    /// The base mode, without verifiability.
+    Oprf,
+    /// The verifiable mode, where the server proves it used the key of its public key.
+    Voprf,
+    /// The partially-oblivious mode, which adds public info to the evaluation.
+    Poprf,
+}
+
+#[derive(Clone, uniffi::Record)]
+pub struct OprfKeyPair {
+    pub private_key: Vec<u8>,
+    pub public_key: Vec<u8>,
+}
+
+#[derive(uniffi::Record)]
+pub struct OprfBlindResult {
+    /// Secret client state, kept by the client until it finalizes.
+    pub client_state: Vec<u8>,
+    /// The blinded element to send to the server.
+    pub blinded_element: Vec<u8>,
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct OprfEvaluateResult {
+    pub evaluated_element: Vec<u8>,
+    /// The DLEQ proof, present in the VOPRF and POPRF modes.
+    pub proof: Option<Vec<u8>>,
+}
+
+#[derive(uniffi::Record)]
+pub struct OprfBatchBlindResult {
+    /// Secret client states, kept by the client until it finalizes.
+    pub client_states: Vec<Vec<u8>>,
+    /// The blinded elements to send to the server, in input order.
+    pub blinded_elements: Vec<Vec<u8>>,
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct OprfBatchEvaluateResult {
+    /// The evaluated elements, in the order of the blinded elements.
+    pub evaluated_elements: Vec<Vec<u8>>,
+    /// The single DLEQ proof covering every element of the batch.
+    pub proof: Vec<u8>,
+}
+
+/// Runs `$body` with `$cs` bound to the voprf ciphersuite type of `$suite`.
+macro_rules! with_suite {
+    ($suite:expr, $cs:ident => $body:expr) => {
+        match $suite {
+            OprfSuite::P256Sha256 => {
+                type $cs = p256::NistP256;
+                $body
+            }
+            OprfSuite::Ristretto255Sha512 => {
+                type $cs = voprf::Ristretto255;
+                $body
+            }
+        }
+    };
+}
+pub(crate) use with_suite;
+
+fn check_info(mode: OprfMode, info: &Option<Vec<u8>>) -> Result<(), OprfError> {
+    if info.is_some() && mode != OprfMode::Poprf {
+        return Err(OprfError::InvalidParameter(
+            "info is only used in the POPRF mode".into(),
+        ));
+    }
+    Ok(())
+}
+
+fn check_batch(mode: OprfMode, len: usize) -> Result<(), OprfError> {
+    if mode == OprfMode::Oprf {
+        return Err(OprfError::InvalidParameter(
+            "batches are only evaluated in the VOPRF and POPRF modes".into(),
+        ));
+    }
+    if len == 0 || len > u16::MAX as usize {
+        return Err(OprfError::InvalidParameter(format!(
+            "a batch must have between 1 and {} elements",
+            u16::MAX
+        )));
+    }
+    Ok(())
+}
+
+fn required<'a>(value: &'a Option<Vec<u8>>, name: &str) -> Result<&'a [u8], OprfError> {
+    value
+        .as_deref()
+        .ok_or_else(|| OprfError::InvalidParameter(format!("{} is required in this mode", name)))
+}
+
+pub(crate) fn key_pair(suite: OprfSuite, private_key: Vec<u8>) -> Result<OprfKeyPair, OprfError> {
+    with_suite!(suite, CS => {
+        let public_key = VoprfServer::<CS>::new_with_key(&private_key)?.get_public_key();
+        Ok(OprfKeyPair {
+            private_key,
+            public_key: CS::serialize_elem(public_key).to_vec(),
+        })
+    })
+}
+
+/// Generates a random key pair for `suite`.
+///
+/// The public key is only needed by clients in the VOPRF and POPRF modes.
+#[uniffi::export]
+pub fn oprf_generate_key_pair(suite: OprfSuite) -> Result<OprfKeyPair, OprfError> {
+    let private_key = with_suite!(suite, CS => {
+        CS::serialize_scalar(CS::random_scalar(&mut OsRng)).to_vec()
+    });
+    key_pair(suite, private_key)
+}
+
+/// Deterministically derives a key pair for `suite` and `mode` from `seed` and `info`,
+/// using `DeriveKeyPair` of RFC 9497.
+#[uniffi::export]
+pub fn oprf_derive_key_pair(
+    suite: OprfSuite,
+    mode: OprfMode,
+    seed: Vec<u8>,
+    info: Vec<u8>,
+) -> Result<OprfKeyPair, OprfError> {
+    let private_key = with_suite!(suite, CS => {
+        // The serialization of the VOPRF and POPRF servers is the private key followed by the
+        // public key, that of the OPRF server the private key alone.
+        let (serialized, public_key_len) = match mode {
+            OprfMode::Oprf => (OprfServer::<CS>::new_from_seed(&seed, &info)?.serialize().to_vec(), 0),
+            OprfMode::Voprf => {
+                let server = VoprfServer::<CS>::new_from_seed(&seed, &info)?;
+                (server.serialize().to_vec(), CS::serialize_elem(server.get_public_key()).len())
+            }
+            OprfMode::Poprf => {
+                let server = PoprfServer::<CS>::new_from_seed(&seed, &info)?;
+                (server.serialize().to_vec(), CS::serialize_elem(server.get_public_key()).len())
+            }
+        };
+        serialized[..serialized.len() - public_key_len].to_vec()
+    });
+    key_pair(suite, private_key)
+}
+
+/// Blinds `input` on the client, starting an evaluation.
+#[uniffi::export]
+pub fn oprf_client_blind(
+    suite: OprfSuite,
+    mode: OprfMode,
+    input: Vec<u8>,
+) -> Result<OprfBlindResult, OprfError> {
+    with_suite!(suite, CS => {
+        let (client_state, blinded_element) = match mode {
+            OprfMode::Oprf => {
+                let result = OprfClient::<CS>::blind(&input, &mut OsRng)?;
+                (result.state.serialize().to_vec(), result.message.serialize().to_vec())
+            }
+            OprfMode::Voprf => {
+                let result = VoprfClient::<CS>::blind(&input, &mut OsRng)?;
+                (result.state.serialize().to_vec(), result.message.serialize().to_vec())
+            }
+            OprfMode::Poprf => {
+                let result = PoprfClient::<CS>::blind(&input, &mut OsRng)?;
+                (result.state.serialize().to_vec(), result.message.serialize().to_vec())
+            }
+        };
+        Ok(OprfBlindResult {
+            client_state,
+            blinded_element,
+        })
+    })
+}
+
+/// Evaluates a blinded element from a client with `private_key`.
+///
+/// `info` is the public input of the POPRF mode and must be `None` in the other modes.
+#[uniffi::export]
+pub fn oprf_server_blind_evaluate(
+    suite: OprfSuite,
+    mode: OprfMode,
+    private_key: Vec<u8>,
+    blinded_element: Vec<u8>,
+    info: Option<Vec<u8>>,
+) -> Result<OprfEvaluateResult, OprfError> {
+    check_info(mode, &info)?;
+    with_suite!(suite, CS => {
+        let blinded_element = BlindedElement::<CS>::deserialize(&blinded_element)?;
+        match mode {
+            OprfMode::Oprf => {
+                let server = OprfServer::<CS>::new_with_key(&private_key)?;
+                Ok(OprfEvaluateResult {
+                    evaluated_element: server.blind_evaluate(&blinded_element).serialize().to_vec(),
+                    proof: None,
+                })
+            }
+            OprfMode::Voprf => {
+                let server = VoprfServer::<CS>::new_with_key(&private_key)?;
+                let result = server.blind_evaluate(&mut OsRng, &blinded_element);
+                Ok(OprfEvaluateResult {
+                    evaluated_element: result.message.serialize().to_vec(),
+                    proof: Some(result.proof.serialize().to_vec()),
+                })
+            }
+            OprfMode::Poprf => {
+                let server = PoprfServer::<CS>::new_with_key(&private_key)?;
+                let result =
+                    server.blind_evaluate(&mut OsRng, &blinded_element, info.as_deref())?;
+                Ok(OprfEvaluateResult {
+                    evaluated_element: result.message.serialize().to_vec(),
+                    proof: Some(result.proof.serialize().to_vec()),
+                })
+            }
+        }
+    })
+}
+
+/// Unblinds the server's evaluation on the client and returns the OPRF output.
+///
+/// In the VOPRF and POPRF modes the proof of `evaluation` is verified against `public_key`,
+/// failing with [`OprfError::ProofVerification`] if the server did not use that key.
+#[uniffi::export]
+pub fn oprf_client_finalize(
+    suite: OprfSuite,
+    mode: OprfMode,
+    client_state: Vec<u8>,
+    input: Vec<u8>,
+    evaluation: OprfEvaluateResult,
+    public_key: Option<Vec<u8>>,
+    info: Option<Vec<u8>>,
+) -> Result<Vec<u8>, OprfError> {
+    check_info(mode, &info)?;
+    with_suite!(suite, CS => {
+        let evaluated_element = EvaluationElement::<CS>::deserialize(&evaluation.evaluated_element)?;
+        let output = match mode {
+            OprfMode::Oprf => {
+                if evaluation.proof.is_some() || public_key.is_some() {
+                    return Err(OprfError::InvalidParameter(
+                        "proof and public_key are not used in the OPRF mode".into(),
+                    ));
+                }
+                OprfClient::<CS>::deserialize(&client_state)?.finalize(&input, &evaluated_element)?
+            }
+            OprfMode::Voprf => {
+                let proof = Proof::<CS>::deserialize(required(&evaluation.proof, "proof")?)?;
+                let public_key = CS::deserialize_elem(required(&public_key, "public_key")?)?;
+                VoprfClient::<CS>::deserialize(&client_state)?.finalize(
+                    &input,
+                    &evaluated_element,
+                    &proof,
+                    public_key,
+                )?
+            }
+            OprfMode::Poprf => {
+                let proof = Proof::<CS>::deserialize(required(&evaluation.proof, "proof")?)?;
+                let public_key = CS::deserialize_elem(required(&public_key, "public_key")?)?;
+                PoprfClient::<CS>::deserialize(&client_state)?.finalize(
+                    &input,
+                    &evaluated_element,
+                    &proof,
+                    public_key,
+                    info.as_deref(),
+                )?
+            }
+        };
+        Ok(output.to_vec())
+    })
+}
+
+/// Computes the OPRF output for `input` directly with `private_key`, without blinding.
+///
+/// This gives the same output as a blinded evaluation, e.g. for a server checking a
+/// value a client derived earlier.
+#[uniffi::export]
+pub fn oprf_server_evaluate(
+    suite: OprfSuite,
+    mode: OprfMode,
+    private_key: Vec<u8>,
+    input: Vec<u8>,
+    info: Option<Vec<u8>>,
+) -> Result<Vec<u8>, OprfError> {
+    check_info(mode, &info)?;
+    with_suite!(suite, CS => {
+        let output = match mode {
+            OprfMode::Oprf => OprfServer::<CS>::new_with_key(&private_key)?.evaluate(&input)?,
+            OprfMode::Voprf => VoprfServer::<CS>::new_with_key(&private_key)?.evaluate(&input)?,
+            OprfMode::Poprf => {
+                PoprfServer::<CS>::new_with_key(&private_key)?.evaluate(&input, info.as_deref())?
+            }
+        };
+        Ok(output.to_vec())
+    })
+}
+
+/// Blinds every input of a batch on the client.
+///
+/// The blinded elements are meant to be evaluated together with
+/// [`oprf_server_batch_blind_evaluate`], so `mode` must be VOPRF or POPRF.
+#[uniffi::export]
+pub fn oprf_client_batch_blind(
+    suite: OprfSuite,
+    mode: OprfMode,
+    inputs: Vec<Vec<u8>>,
+) -> Result<OprfBatchBlindResult, OprfError> {
+    check_batch(mode, inputs.len())?;
+    let mut client_states = Vec::with_capacity(inputs.len());
+    let mut blinded_elements = Vec::with_capacity(inputs.len());
+    for input in inputs {
+        let result = oprf_client_blind(suite, mode, input)?;
+        client_states.push(result.client_state);
+        blinded_elements.push(result.blinded_element);
+    }
+    Ok(OprfBatchBlindResult {
+        client_states,
+        blinded_elements,
+    })
+}
+
+/// Evaluates a batch of blinded elements with `private_key`, proving all of them with a
+/// single DLEQ proof.
+///
+/// `info` is the public input of the POPRF mode and must be `None` in the VOPRF mode.
+#[uniffi::export]
+pub fn oprf_server_batch_blind_evaluate(
+    suite: OprfSuite,
+    mode: OprfMode,
+    private_key: Vec<u8>,
+    blinded_elements: Vec<Vec<u8>>,
+    info: Option<Vec<u8>>,
+) -> Result<OprfBatchEvaluateResult, OprfError> {
+    check_batch(mode, blinded_elements.len())?;
+    check_info(mode, &info)?;
+    with_suite!(suite, CS => {
+        let blinded_elements = blinded_elements
+            .iter()
+            .map(|element| BlindedElement::<CS>::deserialize(element))
+            .collect::<Result<Vec<_>, _>>()?;
+        let (messages, proof) = match mode {
+            OprfMode::Oprf => unreachable!("rejected by check_batch"),
+            OprfMode::Voprf => {
+                let server = VoprfServer::<CS>::new_with_key(&private_key)?;
+                let result = server.batch_blind_evaluate(&mut OsRng, &blinded_elements)?;
+                (result.messages, result.proof)
+            }
+            OprfMode::Poprf => {
+                let server = PoprfServer::<CS>::new_with_key(&private_key)?;
+                let result =
+                    server.batch_blind_evaluate(&mut OsRng, &blinded_elements, info.as_deref())?;
+                (result.messages, result.proof)
+            }
+        };
+        Ok(OprfBatchEvaluateResult {
+            evaluated_elements: messages.iter().map(|m| m.serialize().to_vec()).collect(),
+            proof: proof.serialize().to_vec(),
+        })
+    })
+}
+
+/// Verifies the batch proof of `evaluation` against `public_key` and unblinds every
+/// evaluated element, returning the OPRF outputs in input order.
+///
+/// Fails with [`OprfError::ProofVerification`] if the proof does not cover exactly these
+/// blinded and evaluated elements under that key.
+#[uniffi::export]
+pub fn oprf_client_batch_finalize(
+    suite: OprfSuite,
+    mode: OprfMode,
+    client_states: Vec<Vec<u8>>,
+    inputs: Vec<Vec<u8>>,
+    evaluation: OprfBatchEvaluateResult,
+    public_key: Vec<u8>,
+    info: Option<Vec<u8>>,
+) -> Result<Vec<Vec<u8>>, OprfError> {
+    check_batch(mode, inputs.len())?;
+    check_info(mode, &info)?;
+    if client_states.len() != inputs.len() || evaluation.evaluated_elements.len() != inputs.len() {
+        return Err(OprfError::InvalidParameter(
+            "inputs, client states and evaluated elements must have the same length".into(),
+        ));
+    }
+    with_suite!(suite, CS => {
+        let messages = evaluation
+            .evaluated_elements
+            .iter()
+            .map(|element| EvaluationElement::<CS>::deserialize(element))
+            .collect::<Result<Vec<_>, _>>()?;
+        let proof = Proof::<CS>::deserialize(&evaluation.proof)?;
+        let public_key = CS::deserialize_elem(&public_key)?;
+        let outputs = match mode {
+            OprfMode::Oprf => unreachable!("rejected by check_batch"),
+            OprfMode::Voprf => {
+                let clients = client_states
+                    .iter()
+                    .map(|state| VoprfClient::<CS>::deserialize(state))
+                    .collect::<Result<Vec<_>, _>>()?;
+                VoprfClient::batch_finalize(&inputs, &clients, &messages, &proof, public_key)?
+                    .map(|output| output.map(|o| o.to_vec()))
+                    .collect::<Result<Vec<_>, _>>()?
+            }
+            OprfMode::Poprf => {
+                let clients = client_states
+                    .iter()
+                    .map(|state| PoprfClient::<CS>::deserialize(state))
+                    .collect::<Result<Vec<_>, _>>()?;
+                PoprfClient::batch_finalize(
+                    inputs.iter().map(Vec::as_slice),
+                    &clients,
+                    &messages,
+                    &proof,
+                    public_key,
+                    info.as_deref(),
+                )?
+                .map(|output| output.map(|o| o.to_vec()))
+                .collect::<Result<Vec<_>, _>>()?
+            }
+        };
+        Ok(outputs)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEED: [u8; 32] = [0xa3; 32];
+    const KEY_INFO: &[u8] = b"test key";
+
+    /// Builds the client state RFC 9497 test vectors imply, from the blind and, in the
+    /// verifiable modes, the blinded element.
+    fn vector_client_state(mode: OprfMode, blind: &str, blinded_element: &str) -> Vec<u8> {
+        let mut state = hex::decode(blind).unwrap();
+        if mode != OprfMode::Oprf {
+            state.extend(hex::decode(blinded_element).unwrap());
+        }
+        state
+    }
+
+    struct Vector {
+        suite: OprfSuite,
+        mode: OprfMode,
+        sk: &'static str,
+        pk: Option<&'static str>,
+        info: Option<&'static [u8]>,
+        input: &'static str,
+        blind: &'static str,
+        blinded_element: &'static str,
+        evaluation_element: &'static str,
+        proof: Option<&'static str>,
+        output: &'static str,
+    }
+
+    // RFC 9497 Appendix A.1.1.1, A.3.1.1, A.3.2.1 and A.3.3.1.
+    const VECTORS: &[Vector] = &[
+        Vector {
+            suite: OprfSuite::Ristretto255Sha512,
+            mode: OprfMode::Oprf,
+            sk: "5ebcea5ee37023ccb9fc2d2019f9d7737be85591ae8652ffa9ef0f4d37063b0e",
+            pk: None,
+            info: None,
+            input: "00",
+            blind: "64d37aed22a27f5191de1c1d69fadb899d8862b58eb4220029e036ec4c1f6706",
+            blinded_element: "609a0ae68c15a3cf6903766461307e5c8bb2f95e7e6550e1ffa2dc99e412803c",
+            evaluation_element: "7ec6578ae5120958eb2db1745758ff379e77cb64fe77b0b2d8cc917ea0869c7e",
+            proof: None,
+            output: "527759c3d9366f277d8c6020418d96bb393ba2afb20ff90df23fb7708264e2f3ab9135e3bd69955851de4b1f9fe8a0973396719b7912ba9ee8aa7d0b5e24bcf6",
+        },
+        Vector {
+            suite: OprfSuite::P256Sha256,
+            mode: OprfMode::Oprf,
+            sk: "159749d750713afe245d2d39ccfaae8381c53ce92d098a9375ee70739c7ac0bf",
+            pk: None,
+            info: None,
+            input: "00",
+            blind: "3338fa65ec36e0290022b48eb562889d89dbfa691d1cde91517fa222ed7ad364",
+            blinded_element: "03723a1e5c09b8b9c18d1dcbca29e8007e95f14f4732d9346d490ffc195110368d",
+            evaluation_element: "030de02ffec47a1fd53efcdd1c6faf5bdc270912b8749e783c7ca75bb412958832",
+            proof: None,
+            output: "a0b34de5fa4c5b6da07e72af73cc507cceeb48981b97b7285fc375345fe495dd",
+        },
+        Vector {
+            suite: OprfSuite::P256Sha256,
+            mode: OprfMode::Voprf,
+            sk: "ca5d94c8807817669a51b196c34c1b7f8442fde4334a7121ae4736364312fca6",
+            pk: Some("03e17e70604bcabe198882c0a1f27a92441e774224ed9c702e51dd17038b102462"),
+            info: None,
+            input: "00",
+            blind: "3338fa65ec36e0290022b48eb562889d89dbfa691d1cde91517fa222ed7ad364",
+            blinded_element: "02dd05901038bb31a6fae01828fd8d0e49e35a486b5c5d4b4994013648c01277da",
+            evaluation_element: "0209f33cab60cf8fe69239b0afbcfcd261af4c1c5632624f2e9ba29b90ae83e4a2",
+            proof: Some("e7c2b3c5c954c035949f1f74e6bce2ed539a3be267d1481e9ddb178533df4c2664f69d065c604a4fd953e100b856ad83804eb3845189babfa5a702090d6fc5fa"),
+            output: "0412e8f78b02c415ab3a288e228978376f99927767ff37c5718d420010a645a1",
+        },
+        Vector {
+            suite: OprfSuite::P256Sha256,
+            mode: OprfMode::Poprf,
+            sk: "6ad2173efa689ef2c27772566ad7ff6e2d59b3b196f00219451fb2c89ee4dae2",
+            pk: Some("030d7ff077fddeec965db14b794f0cc1ba9019b04a2f4fcc1fa525dedf72e2a3e3"),
+            info: Some(b"test info"),
+            input: "00",
+            blind: "3338fa65ec36e0290022b48eb562889d89dbfa691d1cde91517fa222ed7ad364",
+            blinded_element: "031563e127099a8f61ed51eeede05d747a8da2be329b40ba1f0db0b2bd9dd4e2c0",
+            evaluation_element: "02c5e5300c2d9e6ba7f3f4ad60500ad93a0157e6288eb04b67e125db024a2c74d2",
+            proof: Some("f8a33690b87736c854eadfcaab58a59b8d9c03b569110b6f31f8bf7577f3fbb85a8a0c38468ccde1ba942be501654adb106167c8eb178703ccb42bccffb9231a"),
+            output: "193a92520bd8fd1f37accb918040a57108daa110dc4f659abe212636d245c592",
+        },
+    ];
+
+    #[test]
+    fn rfc9497_vectors() {
+        for v in VECTORS {
+            let key_pair =
+                oprf_derive_key_pair(v.suite, v.mode, SEED.to_vec(), KEY_INFO.to_vec()).unwrap();
+            assert_eq!(hex::encode(&key_pair.private_key), v.sk);
+            if let Some(pk) = v.pk {
+                assert_eq!(hex::encode(&key_pair.public_key), pk);
+            }
+
+            let info = v.info.map(|i| i.to_vec());
+            let evaluation = oprf_server_blind_evaluate(
+                v.suite,
+                v.mode,
+                key_pair.private_key.clone(),
+                hex::decode(v.blinded_element).unwrap(),
+                info.clone(),
+            )
+            .unwrap();
+            assert_eq!(
+                hex::encode(&evaluation.evaluated_element),
+                v.evaluation_element
+            );
+
+            let input = hex::decode(v.input).unwrap();
+            let output = oprf_client_finalize(
+                v.suite,
+                v.mode,
+                vector_client_state(v.mode, v.blind, v.blinded_element),
+                input.clone(),
+                OprfEvaluateResult {
+                    evaluated_element: evaluation.evaluated_element,
+                    proof: v.proof.map(|p| hex::decode(p).unwrap()),
+                },
+                v.pk.map(|pk| hex::decode(pk).unwrap()),
+                info.clone(),
+            )
+            .unwrap();
+            assert_eq!(hex::encode(&output), v.output);
+
+            let direct =
+                oprf_server_evaluate(v.suite, v.mode, key_pair.private_key, input, info).unwrap();
+            assert_eq!(hex::encode(direct), v.output);
+        }
+    }
+
+    #[test]
+    fn round_trip_in_every_suite_and_mode() {
+        let input = b"input".to_vec();
+        for suite in [OprfSuite::P256Sha256, OprfSuite::Ristretto255Sha512] {
+            for mode in [OprfMode::Oprf, OprfMode::Voprf, OprfMode::Poprf] {
+                let key_pair = oprf_generate_key_pair(suite).unwrap();
+                let info = (mode == OprfMode::Poprf).then(|| b"info".to_vec());
+                let public_key = (mode != OprfMode::Oprf).then(|| key_pair.public_key.clone());
+
+                let blind = oprf_client_blind(suite, mode, input.clone()).unwrap();
+                let evaluation = oprf_server_blind_evaluate(
+                    suite,
+                    mode,
+                    key_pair.private_key.clone(),
+                    blind.blinded_element,
+                    info.clone(),
+                )
+                .unwrap();
+                let output = oprf_client_finalize(
+                    suite,
+                    mode,
+                    blind.client_state,
+                    input.clone(),
+                    evaluation,
+                    public_key,
+                    info.clone(),
+                )
+                .unwrap();
+
+                let direct =
+                    oprf_server_evaluate(suite, mode, key_pair.private_key, input.clone(), info)
+                        .unwrap();
+                assert_eq!(output, direct, "{:?} {:?}", suite, mode);
+            }
+        }
+    }
+
+    #[test]
+    fn wrong_public_key_fails_verification() {
+        let suite = OprfSuite::P256Sha256;
+        let mode = OprfMode::Voprf;
+        let key_pair = oprf_generate_key_pair(suite).unwrap();
+        let other = oprf_generate_key_pair(suite).unwrap();
+
+        let blind = oprf_client_blind(suite, mode, b"input".to_vec()).unwrap();
+        let evaluation = oprf_server_blind_evaluate(
+            suite,
+            mode,
+            key_pair.private_key,
+            blind.blinded_element,
+            None,
+        )
+        .unwrap();
+        let result = oprf_client_finalize(
+            suite,
+            mode,
+            blind.client_state,
+            b"input".to_vec(),
+            evaluation,
+            Some(other.public_key),
+            None,
+        );
+        assert!(matches!(result, Err(OprfError::ProofVerification)));
+    }
+
+    #[test]
+    fn info_outside_poprf_is_err() {
+        let key_pair = oprf_generate_key_pair(OprfSuite::Ristretto255Sha512).unwrap();
+        assert!(matches!(
+            oprf_server_evaluate(
+                OprfSuite::Ristretto255Sha512,
+                OprfMode::Oprf,
+                key_pair.private_key,
+                b"input".to_vec(),
+                Some(b"info".to_vec()),
+            ),
+            Err(OprfError::InvalidParameter(_))
+        ));
+    }
+
+    struct BatchVector {
+        suite: OprfSuite,
+        mode: OprfMode,
+        pk: &'static str,
+        info: Option<&'static [u8]>,
+        inputs: [&'static str; 2],
+        blinds: [&'static str; 2],
+        blinded_elements: [&'static str; 2],
+        evaluation_elements: [&'static str; 2],
+        proof: &'static str,
+        outputs: [&'static str; 2],
+    }
+
+    // RFC 9497 Appendix A.1.2.3, A.3.2.3 and A.3.3.3.
+    const BATCH_VECTORS: &[BatchVector] = &[
+        BatchVector {
+            suite: OprfSuite::Ristretto255Sha512,
+            mode: OprfMode::Voprf,
+            pk: "c803e2cc6b05fc15064549b5920659ca4a77b2cca6f04f6b357009335476ad4e",
+            info: None,
+            inputs: ["00", "5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a"],
+            blinds: [
+                "64d37aed22a27f5191de1c1d69fadb899d8862b58eb4220029e036ec4c1f6706",
+                "222a5e897cf59db8145db8d16e597e8facb80ae7d4e26d9881aa6f61d645fc0e",
+            ],
+            blinded_elements: [
+                "863f330cc1a1259ed5a5998a23acfd37fb4351a793a5b3c090b642ddc439b945",
+                "90a0145ea9da29254c3a56be4fe185465ebb3bf2a1801f7124bbbadac751e654",
+            ],
+            evaluation_elements: [
+                "aa8fa048764d5623868679402ff6108d2521884fa138cd7f9c7669a9a014267e",
+                "cc5ac221950a49ceaa73c8db41b82c20372a4c8d63e5dded2db920b7eee36a2a",
+            ],
+            proof: "cc203910175d786927eeb44ea847328047892ddf8590e723c37205cb74600b0a5ab5337c8eb4ceae0494c2cf89529dcf94572ed267473d567aeed6ab873dee08",
+            outputs: [
+                "b58cfbe118e0cb94d79b5fd6a6dafb98764dff49c14e1770b566e42402da1a7da4d8527693914139caee5bd03903af43a491351d23b430948dd50cde10d32b3c",
+                "8a9a2f3c7f085b65933594309041fc1898d42d0858e59f90814ae90571a6df60356f4610bf816f27afdd84f47719e480906d27ecd994985890e5f539e7ea74b6",
+            ],
+        },
+        BatchVector {
+            suite: OprfSuite::P256Sha256,
+            mode: OprfMode::Voprf,
+            pk: "03e17e70604bcabe198882c0a1f27a92441e774224ed9c702e51dd17038b102462",
+            info: None,
+            inputs: ["00", "5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a"],
+            blinds: [
+                "3338fa65ec36e0290022b48eb562889d89dbfa691d1cde91517fa222ed7ad364",
+                "f9db001266677f62c095021db018cd8cbb55941d4073698ce45c405d1348b7b1",
+            ],
+            blinded_elements: [
+                "02dd05901038bb31a6fae01828fd8d0e49e35a486b5c5d4b4994013648c01277da",
+                "03462e9ae64cae5b83ba98a6b360d942266389ac369b923eb3d557213b1922f8ab",
+            ],
+            evaluation_elements: [
+                "0209f33cab60cf8fe69239b0afbcfcd261af4c1c5632624f2e9ba29b90ae83e4a2",
+                "02bb24f4d838414aef052a8f044a6771230ca69c0a5677540fff738dd31bb69771",
+            ],
+            proof: "bdcc351707d02a72ce49511c7db990566d29d6153ad6f8982fad2b435d6ce4d60da1e6b3fa740811bde34dd4fe0aa1b5fe6600d0440c9ddee95ea7fad7a60cf2",
+            outputs: [
+                "0412e8f78b02c415ab3a288e228978376f99927767ff37c5718d420010a645a1",
+                "771e10dcd6bcd3664e23b8f2a710cfaaa8357747c4a8cbba03133967b5c24f18",
+            ],
+        },
+        BatchVector {
+            suite: OprfSuite::P256Sha256,
+            mode: OprfMode::Poprf,
+            pk: "030d7ff077fddeec965db14b794f0cc1ba9019b04a2f4fcc1fa525dedf72e2a3e3",
+            info: Some(b"test info"),
+            inputs: ["00", "5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a"],
+            blinds: [
+                "3338fa65ec36e0290022b48eb562889d89dbfa691d1cde91517fa222ed7ad364",
+                "f9db001266677f62c095021db018cd8cbb55941d4073698ce45c405d1348b7b1",
+            ],
+            blinded_elements: [
+                "031563e127099a8f61ed51eeede05d747a8da2be329b40ba1f0db0b2bd9dd4e2c0",
+                "03ca4ff41c12fadd7a0bc92cf856732b21df652e01a3abdf0fa8847da053db213c",
+            ],
+            evaluation_elements: [
+                "02c5e5300c2d9e6ba7f3f4ad60500ad93a0157e6288eb04b67e125db024a2c74d2",
+                "02f0b6bcd467343a8d8555a99dc2eed0215c71898c5edb77a3d97ddd0dbad478e8",
+            ],
+            proof: "8fbd85a32c13aba79db4b42e762c00687d6dbf9c8cb97b2a225645ccb00d9d7580b383c885cdfd07df448d55e06f50f6173405eee5506c0ed0851ff718d13e68",
+            outputs: [
+                "193a92520bd8fd1f37accb918040a57108daa110dc4f659abe212636d245c592",
+                "1e6d164cfd835d88a31401623549bf6b9b306628ef03a7962921d62bc5ffce8c",
+            ],
+        },
+    ];
+
+    fn decode_all(values: &[&str]) -> Vec<Vec<u8>> {
+        values.iter().map(|v| hex::decode(v).unwrap()).collect()
+    }
+
+    #[test]
+    fn rfc9497_batch_vectors() {
+        for v in BATCH_VECTORS {
+            let key_pair =
+                oprf_derive_key_pair(v.suite, v.mode, SEED.to_vec(), KEY_INFO.to_vec()).unwrap();
+            assert_eq!(hex::encode(&key_pair.public_key), v.pk);
+
+            let info = v.info.map(|i| i.to_vec());
+            let evaluation = oprf_server_batch_blind_evaluate(
+                v.suite,
+                v.mode,
+                key_pair.private_key,
+                decode_all(&v.blinded_elements),
+                info.clone(),
+            )
+            .unwrap();
+            assert_eq!(
+                evaluation.evaluated_elements,
+                decode_all(&v.evaluation_elements)
+            );
+
+            let client_states = v
+                .blinds
+                .iter()
+                .zip(v.blinded_elements)
+                .map(|(blind, element)| vector_client_state(v.mode, blind, element))
+                .collect();
+            let outputs = oprf_client_batch_finalize(
+                v.suite,
+                v.mode,
+                client_states,
+                decode_all(&v.inputs),
+                OprfBatchEvaluateResult {
+                    evaluated_elements: evaluation.evaluated_elements,
+                    proof: hex::decode(v.proof).unwrap(),
+                },
+                key_pair.public_key,
+                info,
+            )
+            .unwrap();
+            assert_eq!(outputs, decode_all(&v.outputs));
+        }
+    }
+
+    #[test]
+    fn batch_round_trip_matches_single_evaluation() {
+        let inputs: Vec<Vec<u8>> = (0u8..5).map(|i| vec![i; 8]).collect();
+        for suite in [OprfSuite::P256Sha256, OprfSuite::Ristretto255Sha512] {
+            for mode in [OprfMode::Voprf, OprfMode::Poprf] {
+                let key_pair = oprf_generate_key_pair(suite).unwrap();
+                let info = (mode == OprfMode::Poprf).then(|| b"info".to_vec());
+
+                let blind = oprf_client_batch_blind(suite, mode, inputs.clone()).unwrap();
+                let evaluation = oprf_server_batch_blind_evaluate(
+                    suite,
+                    mode,
+                    key_pair.private_key.clone(),
+                    blind.blinded_elements,
+                    info.clone(),
+                )
+                .unwrap();
+                let outputs = oprf_client_batch_finalize(
+                    suite,
+                    mode,
+                    blind.client_states,
+                    inputs.clone(),
+                    evaluation,
+                    key_pair.public_key,
+                    info.clone(),
+                )
+                .unwrap();
+
+                for (input, output) in inputs.iter().zip(outputs) {
+                    let direct = oprf_server_evaluate(
+                        suite,
+                        mode,
+                        key_pair.private_key.clone(),
+                        input.clone(),
+                        info.clone(),
+                    )
+                    .unwrap();
+                    assert_eq!(output, direct, "{:?} {:?}", suite, mode);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn reordered_batch_fails_verification() {
+        let suite = OprfSuite::P256Sha256;
+        let mode = OprfMode::Voprf;
+        let key_pair = oprf_generate_key_pair(suite).unwrap();
+        let inputs = vec![b"first".to_vec(), b"second".to_vec()];
+
+        let blind = oprf_client_batch_blind(suite, mode, inputs.clone()).unwrap();
+        let mut evaluation = oprf_server_batch_blind_evaluate(
+            suite,
+            mode,
+            key_pair.private_key,
+            blind.blinded_elements,
+            None,
+        )
+        .unwrap();
+        evaluation.evaluated_elements.swap(0, 1);
+
+        let result = oprf_client_batch_finalize(
+            suite,
+            mode,
+            blind.client_states,
+            inputs,
+            evaluation,
+            key_pair.public_key,
+            None,
+        );
+        assert!(matches!(result, Err(OprfError::ProofVerification)));
+    }
+
+    #[test]
+    fn batch_in_oprf_mode_is_err() {
+        assert!(matches!(
+            oprf_client_batch_blind(OprfSuite::P256Sha256, OprfMode::Oprf, vec![b"a".to_vec()]),
+            Err(OprfError::InvalidParameter(_))
+        ));
+    }
+}
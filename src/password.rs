@@ -0,0 +1,541 @@
+// SPDX-FileCopyrightText: 2026 Digg - Agency for Digital Government
+//
+// SPDX-License-Identifier: EUPL-1.2
+
+//! This module prepares passwords before they are passed to `client_registration_start`,
+//! `client_login_start` and the client finish functions.
+//!
+//! OPAQUE hashes the password bytes as they are, so a password typed as "café" in NFC on
+//! one platform and in NFD on another gives different OPRF outputs and the login fails.
+//! [`password_prepare_registration`] and [`password_prepare_login`] normalize the password
+//! the same way on every platform, and registration can also reject weak passwords with an
+//! estimate of their strength.
+
+use icu_properties::{
+    props::{
+        CanonicalCombiningClass, DefaultIgnorableCodePoint, GeneralCategory, HangulSyllableType,
+        JoiningType, NoncharacterCodePoint, Script,
+    },
+    CodePointMapData, CodePointSetData,
+};
+use unicode_normalization::UnicodeNormalization;
+
+/// Passwords that are among the most common ones, compared case-insensitively.
+const COMMON_PASSWORDS: &[&str] = &[
+    "123456",
+    "12345678",
+    "123456789",
+    "1234567890",
+    "password",
+    "password1",
+    "qwerty",
+    "qwertyuiop",
+    "abc123",
+    "111111",
+    "iloveyou",
+    "admin",
+    "welcome",
+    "letmein",
+    "monkey",
+    "dragon",
+    "sunshine",
+    "princess",
+    "football",
+    "baseball",
+    "master",
+    "shadow",
+    "superman",
+    "trustno1",
+    "passw0rd",
+    "hunter2",
+    "secret",
+    "sommar",
+    "hejsan",
+    "losenord",
+];
+
+/// The number of characters of the lowercase, uppercase, digit, ASCII symbol and
+/// non-ASCII classes.
+const CLASS_SIZES: [u32; 5] = [26, 26, 10, 33, 100];
+
+#[derive(Debug, thiserror::Error, uniffi::Error)]
+pub enum PasswordError {
+    #[error("Invalid password: {0}")]
+    InvalidPassword(String),
+    #[error("Password is too weak: {score:?}")]
+    TooWeak { score: PasswordScore },
+}
+
+/// How a password is normalized before it is used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum PasswordNormalization {
+    /// The UTF-8 bytes of the password as given.
+    None,
+    /// Unicode NFKC, which also folds compatibility characters such as full-width letters.
+    Nfkc,
+    /// The PRECIS OpaqueString profile (RFC 8265): non-ASCII spaces are mapped to U+0020,
+    /// the password is normalized to NFC, and empty passwords and passwords with code points
+    /// the FreeformClass (RFC 8264) disallows, such as unassigned, default-ignorable and
+    /// control code points, are rejected.
+    OpaqueString,
+}
+
+/// The estimated strength of a password, from weakest to strongest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, uniffi::Enum)]
+pub enum PasswordScore {
+    VeryWeak,
+    Weak,
+    Fair,
+    Strong,
+    VeryStrong,
+}
+
+/// A pattern that makes a password easier to guess.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum PasswordWeakness {
+    /// Shorter than 8 characters.
+    TooShort,
+    /// One of the most common passwords, possibly followed by digits or symbols.
+    CommonPassword,
+    /// A character repeated three or more times in a row.
+    Repetition,
+    /// Three or more consecutive characters, e.g. `abc` or `321`.
+    Sequence,
+    /// Characters of only one class, e.g. only lowercase letters.
+    FewCharacterClasses,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct PasswordStrength {
+    pub score: PasswordScore,
+    /// The estimated number of bits of guessing entropy.
+    pub entropy_bits: u32,
+    pub weaknesses: Vec<PasswordWeakness>,
+}
+
+/// How registration prepares a password.
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct PasswordPolicy {
+    pub normalization: PasswordNormalization,
+    /// The weakest score accepted at registration, or `None` to accept any password.
+    pub minimum_score: Option<PasswordScore>,
+}
+
+fn normalize(
+    password: &str,
+    normalization: PasswordNormalization,
+) -> Result<String, PasswordError> {
+    match normalization {
+        PasswordNormalization::None => Ok(password.to_string()),
+        PasswordNormalization::Nfkc => Ok(password.nfkc().collect()),
+        PasswordNormalization::OpaqueString => {
+            let mapped: String = password
+                .chars()
+                .map(|c| match c {
+                    '\u{00a0}'
+                    | '\u{1680}'
+                    | '\u{2000}'..='\u{200a}'
+                    | '\u{202f}'
+                    | '\u{205f}'
+                    | '\u{3000}' => ' ',
+                    c => c,
+                })
+                .nfc()
+                .collect();
+            if mapped.is_empty() {
+                return Err(PasswordError::InvalidPassword(
+                    "the password is empty".into(),
+                ));
+            }
+            let chars: Vec<char> = mapped.chars().collect();
+            for i in 0..chars.len() {
+                if let Some(reason) = freeform_class_violation(&chars, i) {
+                    return Err(PasswordError::InvalidPassword(format!(
+                        "the password contains U+{:04X}, {}",
+                        chars[i] as u32, reason
+                    )));
+                }
+            }
+            Ok(mapped)
+        }
+    }
+}
+
+/// Whether `chars[i]` satisfies its contextual rule (RFC 5892, Appendix A), or `None` if it
+/// has none.
+fn contextual_rule(chars: &[char], i: usize) -> Option<bool> {
+    let script = |c: char| CodePointMapData::<Script>::new().get(c);
+    let joining_type = |c: char| CodePointMapData::<JoiningType>::new().get(c);
+    let is_virama = |c: char| {
+        CodePointMapData::<CanonicalCombiningClass>::new().get(c) == CanonicalCombiningClass::Virama
+    };
+    let previous = i.checked_sub(1).map(|j| chars[j]);
+    let next = chars.get(i + 1).copied();
+
+    Some(match chars[i] {
+        // ZERO WIDTH NON-JOINER: after a virama, or between a left- or dual-joining and a
+        // right- or dual-joining character, skipping transparent ones
+        '\u{200c}' => {
+            let not_transparent = |c: &&char| joining_type(**c) != JoiningType::Transparent;
+            let joins = |c: Option<&char>, types: [JoiningType; 2]| {
+                c.is_some_and(|c| types.contains(&joining_type(*c)))
+            };
+            previous.is_some_and(is_virama)
+                || joins(
+                    chars[..i].iter().rev().find(not_transparent),
+                    [JoiningType::LeftJoining, JoiningType::DualJoining],
+                ) && joins(
+                    chars[i + 1..].iter().find(not_transparent),
+                    [JoiningType::RightJoining, JoiningType::DualJoining],
+                )
+        }
+        // ZERO WIDTH JOINER
+        '\u{200d}' => previous.is_some_and(is_virama),
+        // MIDDLE DOT
+        '\u{00b7}' => previous == Some('l') && next == Some('l'),
+        // GREEK LOWER NUMERAL SIGN
+        '\u{0375}' => next.is_some_and(|c| script(c) == Script::Greek),
+        // HEBREW PUNCTUATION GERESH and GERSHAYIM
+        '\u{05f3}' | '\u{05f4}' => previous.is_some_and(|c| script(c) == Script::Hebrew),
+        // KATAKANA MIDDLE DOT
+        '\u{30fb}' => chars
+            .iter()
+            .any(|&c| [Script::Hiragana, Script::Katakana, Script::Han].contains(&script(c))),
+        // ARABIC-INDIC and EXTENDED ARABIC-INDIC DIGITS, which must not be mixed
+        '\u{0660}'..='\u{0669}' => !chars.iter().any(|c| ('\u{06f0}'..='\u{06f9}').contains(c)),
+        '\u{06f0}'..='\u{06f9}' => !chars.iter().any(|c| ('\u{0660}'..='\u{0669}').contains(c)),
+        _ => return None,
+    })
+}
+
+/// Why `chars[i]` is not allowed by the PRECIS FreeformClass (RFC 8264, Sections 4.3 and
+/// 8), or `None` if it is.
+fn freeform_class_violation(chars: &[char], i: usize) -> Option<&'static str> {
+    let c = chars[i];
+    if let Some(satisfied) = contextual_rule(chars, i) {
+        return (!satisfied).then_some("which is not allowed in its context");
+    }
+    match c {
+        // The exceptions of RFC 5892, Section 2.6
+        '\u{00df}' | '\u{03c2}' | '\u{06fd}' | '\u{06fe}' | '\u{0f0b}' | '\u{3007}' => return None,
+        '\u{0640}'
+        | '\u{07fa}'
+        | '\u{302e}'
+        | '\u{302f}'
+        | '\u{3031}'..='\u{3035}'
+        | '\u{303b}' => return Some("which is disallowed"),
+        _ => {}
+    }
+
+    let general_category = CodePointMapData::<GeneralCategory>::new().get(c);
+    let noncharacter = CodePointSetData::new::<NoncharacterCodePoint>().contains(c);
+    if general_category == GeneralCategory::Unassigned && !noncharacter {
+        return Some("which is unassigned");
+    }
+    if c.is_ascii_graphic() {
+        return None;
+    }
+    if matches!(
+        CodePointMapData::<HangulSyllableType>::new().get(c),
+        HangulSyllableType::LeadingJamo
+            | HangulSyllableType::VowelJamo
+            | HangulSyllableType::TrailingJamo
+    ) {
+        return Some("an old Hangul jamo");
+    }
+    if noncharacter || CodePointSetData::new::<DefaultIgnorableCodePoint>().contains(c) {
+        return Some("which is default-ignorable or a noncharacter");
+    }
+    if general_category == GeneralCategory::Control {
+        return Some("a control character");
+    }
+    // Code points with compatibility decompositions are valid in the FreeformClass.
+    if !c.to_string().nfkc().eq([c]) {
+        return None;
+    }
+    match general_category {
+        GeneralCategory::Format
+        | GeneralCategory::PrivateUse
+        | GeneralCategory::Surrogate
+        | GeneralCategory::LineSeparator
+        | GeneralCategory::ParagraphSeparator => Some("which is disallowed"),
+        _ => None,
+    }
+}
+
+/// The index of the character class of `c`, in the order of `CLASS_SIZES`.
+fn class(c: char) -> u8 {
+    match c {
+        'a'..='z' => 0,
+        'A'..='Z' => 1,
+        '0'..='9' => 2,
+        c if c.is_ascii() => 3,
+        _ => 4,
+    }
+}
+
+/// Estimates the strength of a password.
+///
+/// The estimate is the entropy of a random password of the same length over the character
+/// classes used, where a character that repeats or continues a sequence counts as one bit,
+/// and is capped for common passwords. It is a heuristic meant to reject the weakest
+/// passwords, not a measure of how long guessing takes.
+#[uniffi::export]
+pub fn password_estimate_strength(password: String) -> PasswordStrength {
+    let chars: Vec<char> = password.chars().collect();
+    let mut weaknesses = Vec::new();
+    if chars.len() < 8 {
+        weaknesses.push(PasswordWeakness::TooShort);
+    }
+
+    let mut classes = [false; 5];
+    for &c in &chars {
+        classes[class(c) as usize] = true;
+    }
+    let alphabet: u32 = CLASS_SIZES
+        .iter()
+        .zip(classes)
+        .filter(|(_, used)| *used)
+        .map(|(size, _)| size)
+        .sum();
+    if classes.iter().filter(|used| **used).count() == 1 {
+        weaknesses.push(PasswordWeakness::FewCharacterClasses);
+    }
+    let bits_per_char = (alphabet as f64).log2();
+
+    let mut entropy = 0.0;
+    let (mut repeat_run, mut sequence_run) = (1, 1);
+    for (i, &c) in chars.iter().enumerate() {
+        let previous = i.checked_sub(1).map(|j| chars[j]);
+        let delta = previous.map(|p| c as i64 - p as i64);
+        repeat_run = if delta == Some(0) { repeat_run + 1 } else { 1 };
+        let continues_sequence = match (i.checked_sub(2).map(|j| chars[j]), delta) {
+            (Some(p), Some(d)) if d.abs() == 1 => (chars[i - 1] as i64 - p as i64) == d,
+            _ => false,
+        };
+        sequence_run = if continues_sequence {
+            sequence_run + 1
+        } else if delta.is_some_and(|d| d.abs() == 1) {
+            2
+        } else {
+            1
+        };
+
+        if repeat_run == 3 && !weaknesses.contains(&PasswordWeakness::Repetition) {
+            weaknesses.push(PasswordWeakness::Repetition);
+        }
+        if sequence_run == 3 && !weaknesses.contains(&PasswordWeakness::Sequence) {
+            weaknesses.push(PasswordWeakness::Sequence);
+        }
+        entropy += if repeat_run > 1 || sequence_run > 1 {
+            1.0
+        } else {
+            bits_per_char
+        };
+    }
+
+    let lowercase = password.to_lowercase();
+    let base = lowercase.trim_end_matches(|c: char| !c.is_alphabetic());
+    if COMMON_PASSWORDS.contains(&lowercase.as_str()) || COMMON_PASSWORDS.contains(&base) {
+        weaknesses.push(PasswordWeakness::CommonPassword);
+        // A guess from the list, plus the digits or symbols appended to it.
+        let suffix_len = lowercase.chars().count() - base.chars().count();
+        entropy = entropy.min(10.0 + 4.0 * suffix_len as f64);
+    }
+
+    let score = match entropy {
+        e if e < 25.0 => PasswordScore::VeryWeak,
+        e if e < 40.0 => PasswordScore::Weak,
+        e if e < 60.0 => PasswordScore::Fair,
+        e if e < 80.0 => PasswordScore::Strong,
+        _ => PasswordScore::VeryStrong,
+    };
+    PasswordStrength {
+        score,
+        entropy_bits: entropy as u32,
+        weaknesses,
+    }
+}
+
+/// Normalizes a password for registration, rejecting it with `TooWeak` if its strength is
+/// below the policy's minimum score.
+///
+/// The strength is estimated on the normalized password. The result is the password
+/// passed to `client_registration_start` and `client_registration_finish`.
+#[uniffi::export]
+pub fn password_prepare_registration(
+    password: String,
+    policy: PasswordPolicy,
+) -> Result<Vec<u8>, PasswordError> {
+    let normalized = normalize(&password, policy.normalization)?;
+    if let Some(minimum_score) = policy.minimum_score {
+        let score = password_estimate_strength(normalized.clone()).score;
+        if score < minimum_score {
+            return Err(PasswordError::TooWeak { score });
+        }
+    }
+    Ok(normalized.into_bytes())
+}
+
+/// Normalizes a password for login, with the normalization it was registered with.
+///
+/// The result is the password passed to `client_login_start` and `client_login_finish`.
+/// Strength is not checked, so a password registered before a stricter policy still works.
+#[uniffi::export]
+pub fn password_prepare_login(
+    password: String,
+    normalization: PasswordNormalization,
+) -> Result<Vec<u8>, PasswordError> {
+    Ok(normalize(&password, normalization)?.into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalization() {
+        let nfc = "caf\u{e9}".to_string();
+        let nfd = "cafe\u{301}".to_string();
+        for normalization in [
+            PasswordNormalization::Nfkc,
+            PasswordNormalization::OpaqueString,
+        ] {
+            assert_eq!(
+                password_prepare_login(nfc.clone(), normalization).unwrap(),
+                password_prepare_login(nfd.clone(), normalization).unwrap()
+            );
+        }
+        assert_ne!(
+            password_prepare_login(nfc, PasswordNormalization::None).unwrap(),
+            password_prepare_login(nfd, PasswordNormalization::None).unwrap()
+        );
+
+        // NFKC folds full-width letters, OpaqueString keeps them
+        assert_eq!(
+            password_prepare_login("\u{ff41}b".into(), PasswordNormalization::Nfkc).unwrap(),
+            b"ab"
+        );
+        assert_ne!(
+            password_prepare_login("\u{ff41}b".into(), PasswordNormalization::OpaqueString)
+                .unwrap(),
+            b"ab"
+        );
+        assert_eq!(
+            password_prepare_login("a\u{3000}b".into(), PasswordNormalization::OpaqueString)
+                .unwrap(),
+            b"a b"
+        );
+    }
+
+    #[test]
+    fn test_opaque_string_rejects_invalid_passwords() {
+        for password in [
+            "",
+            "pass\u{0}word",
+            "pass\nword",
+            // Default-ignorable
+            "pass\u{200b}word",
+            "pass\u{feff}word",
+            "pass\u{ad}word",
+            // Unassigned, a noncharacter, private use and a line separator
+            "pass\u{378}word",
+            "pass\u{fdd0}word",
+            "pass\u{e000}word",
+            "pass\u{2028}word",
+            // An old Hangul jamo and an exception of RFC 5892
+            "\u{1100}",
+            "pass\u{640}word",
+            // Characters whose contextual rules are not met
+            "pass\u{200c}word",
+            "pass\u{200d}word",
+            "a\u{b7}b",
+            "\u{660}\u{6f0}",
+        ] {
+            assert!(
+                matches!(
+                    password_prepare_login(password.into(), PasswordNormalization::OpaqueString),
+                    Err(PasswordError::InvalidPassword(_))
+                ),
+                "{:?}",
+                password
+            );
+        }
+    }
+
+    #[test]
+    fn test_opaque_string_accepts_contextual_characters_in_context() {
+        for password in [
+            "Stra\u{df}e",
+            "l\u{b7}l",
+            // ZWNJ after a Devanagari virama, and between Persian joining letters
+            "\u{915}\u{94d}\u{200c}\u{937}",
+            "\u{645}\u{6cc}\u{200c}\u{62e}\u{648}\u{627}\u{647}\u{645}",
+            "\u{30ab}\u{30fb}\u{30ab}",
+            "\u{660}\u{661}",
+            // Full-width letters have compatibility decompositions
+            "\u{ff41}b",
+        ] {
+            assert!(
+                password_prepare_login(password.into(), PasswordNormalization::OpaqueString)
+                    .is_ok(),
+                "{:?}",
+                password
+            );
+        }
+    }
+
+    #[test]
+    fn test_strength_estimate() {
+        let strength = |password: &str| password_estimate_strength(password.into());
+
+        assert_eq!(strength("password").score, PasswordScore::VeryWeak);
+        assert!(strength("Password123!")
+            .weaknesses
+            .contains(&PasswordWeakness::CommonPassword));
+        assert_eq!(strength("aaaaaaaaaaaa").score, PasswordScore::VeryWeak);
+        assert!(strength("aaaaaaaaaaaa")
+            .weaknesses
+            .contains(&PasswordWeakness::Repetition));
+        assert!(strength("xyzabcdefgh")
+            .weaknesses
+            .contains(&PasswordWeakness::Sequence));
+        assert!(strength("k3!")
+            .weaknesses
+            .contains(&PasswordWeakness::TooShort));
+        assert!(strength("Tr0ub4dor&3").score >= PasswordScore::Strong);
+        assert_eq!(
+            strength("correct horse battery staple").score,
+            PasswordScore::VeryStrong
+        );
+        assert!(strength("correct horse battery staple")
+            .weaknesses
+            .is_empty());
+    }
+
+    #[test]
+    fn test_registration_enforces_minimum_score() {
+        let policy = PasswordPolicy {
+            normalization: PasswordNormalization::OpaqueString,
+            minimum_score: Some(PasswordScore::Fair),
+        };
+        assert!(matches!(
+            password_prepare_registration("letmein".into(), policy.clone()),
+            Err(PasswordError::TooWeak {
+                score: PasswordScore::VeryWeak
+            })
+        ));
+        assert_eq!(
+            password_prepare_registration("correct horse battery staple".into(), policy).unwrap(),
+            b"correct horse battery staple"
+        );
+        assert!(password_prepare_registration(
+            "letmein".into(),
+            PasswordPolicy {
+                normalization: PasswordNormalization::OpaqueString,
+                minimum_score: None,
+            }
+        )
+        .is_ok());
+    }
+}
@@ -0,0 +1,620 @@
+// SPDX-FileCopyrightText: 2026 Digg - Agency for Digital Government
+//
+// SPDX-License-Identifier: EUPL-1.2
+
+//! This module implements issuance and redemption of Privacy Pass privately verifiable
+//! tokens, token type 0x0001 of RFC 9578, built on the P-384 VOPRF of RFC 9497.
+//!
+//! The issuer and the origin redeeming tokens share the issuer keys, so both sides are
+//! served by [`TokenIssuer`]. Spent tokens are tracked by a caller-provided
+//! [`SpentTokenStore`].
+
+use p384::NistP384;
+use rand::{rngs::OsRng, RngCore};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+};
+use subtle::ConstantTimeEq;
+use voprf::{BlindedElement, EvaluationElement, Group, Proof, VoprfClient, VoprfServer};
+
+/// The token type of privately verifiable tokens with the P-384 VOPRF.
+pub const PRIVATE_TOKEN_TYPE: u16 = 0x0001;
+
+// Field lengths of token type 0x0001, named as in RFC 9578.
+/// Length of a serialized P-384 element.
+const NE: usize = 49;
+/// Length of a serialized P-384 scalar.
+const NS: usize = 48;
+/// Length of the authenticator, the VOPRF output.
+const NK: usize = 48;
+/// Length of a token key ID.
+const NID: usize = 32;
+/// Length of the client nonce.
+const NONCE_LEN: usize = 32;
+/// Length of a challenge digest.
+const DIGEST_LEN: usize = 32;
+/// Length of a redemption context, when one is present.
+const REDEMPTION_CONTEXT_LEN: usize = 32;
+
+/// Length of the token input, the token without its authenticator.
+const TOKEN_INPUT_LEN: usize = 2 + NONCE_LEN + DIGEST_LEN + NID;
+const TOKEN_REQUEST_LEN: usize = 2 + 1 + NE;
+const TOKEN_RESPONSE_LEN: usize = NE + 2 * NS;
+const TOKEN_LEN: usize = TOKEN_INPUT_LEN + NK;
+/// Length of the serialized VOPRF client state, the blind followed by the blinded element.
+const VOPRF_CLIENT_LEN: usize = NS + NE;
+/// Length of the client state: the token input, the issuer public key and the VOPRF client.
+const CLIENT_STATE_LEN: usize = TOKEN_INPUT_LEN + NE + VOPRF_CLIENT_LEN;
+
+#[derive(Debug, thiserror::Error, uniffi::Error)]
+pub enum PrivacyPassError {
+    #[error("Privacy Pass failed: {0}")]
+    Generic(String),
+    #[error("Malformed {0}")]
+    Malformed(String),
+    #[error("Unsupported token type {0:#06x}")]
+    UnsupportedTokenType(u16),
+    #[error("No issuer key matches the token key ID")]
+    UnknownKey,
+    #[error("An issuer key with the same truncated token key ID is already in use")]
+    KeyIdCollision,
+    #[error("Token is invalid")]
+    InvalidToken,
+    #[error("Token has already been redeemed")]
+    DoubleSpend,
+    #[error("Spent token store failed: {0}")]
+    Store(String),
+}
+
+impl From<voprf::Error> for PrivacyPassError {
+    fn from(e: voprf::Error) -> Self {
+        PrivacyPassError::Generic(format!("{:?}", e))
+    }
+}
+
+impl From<uniffi::UnexpectedUniFFICallbackError> for PrivacyPassError {
+    fn from(e: uniffi::UnexpectedUniFFICallbackError) -> Self {
+        PrivacyPassError::Store(e.reason)
+    }
+}
+
+/// The challenge an origin sends to clients, in the `WWW-Authenticate` header.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct TokenChallenge {
+    pub token_type: u16,
+    pub issuer_name: String,
+    /// Either empty or 32 bytes, binding tokens to e.g. a session.
+    pub redemption_context: Vec<u8>,
+    /// The origins the token may be redeemed at, empty for any origin.
+    pub origin_info: Vec<String>,
+}
+
+#[derive(Clone, uniffi::Record)]
+pub struct IssuerKey {
+    pub private_key: Vec<u8>,
+    pub public_key: Vec<u8>,
+    /// SHA-256 of the public key, identifying the key in tokens.
+    pub token_key_id: Vec<u8>,
+}
+
+#[derive(uniffi::Record)]
+pub struct TokenRequestResult {
+    /// The TokenRequest to send to the issuer.
+    pub token_request: Vec<u8>,
+    /// Secret client state, kept by the client until the TokenResponse arrives.
+    pub client_state: Vec<u8>,
+}
+
+/// Records which tokens have been redeemed.
+#[uniffi::export(with_foreign)]
+pub trait SpentTokenStore: Send + Sync {
+    /// Marks the token with `nonce` under `token_key_id` as spent, returning `false` if it
+    /// already was.
+    ///
+    /// Implementations must check and insert atomically, or a token can be spent twice.
+    fn mark_spent(&self, token_key_id: Vec<u8>, nonce: Vec<u8>) -> Result<bool, PrivacyPassError>;
+}
+
+struct MemorySpentTokenStore {
+    spent: Mutex<HashSet<Vec<u8>>>,
+}
+
+impl SpentTokenStore for MemorySpentTokenStore {
+    fn mark_spent(&self, token_key_id: Vec<u8>, nonce: Vec<u8>) -> Result<bool, PrivacyPassError> {
+        let mut spent = self
+            .spent
+            .lock()
+            .map_err(|e| PrivacyPassError::Store(format!("{:?}", e)))?;
+        Ok(spent.insert([token_key_id, nonce].concat()))
+    }
+}
+
+/// Returns a [`SpentTokenStore`] kept in memory, for tests and single-process deployments.
+#[uniffi::export]
+pub fn memory_spent_token_store() -> Arc<dyn SpentTokenStore> {
+    Arc::new(MemorySpentTokenStore {
+        spent: Mutex::new(HashSet::new()),
+    })
+}
+
+/// Serializes a token challenge.
+#[uniffi::export]
+pub fn encode_token_challenge(challenge: TokenChallenge) -> Result<Vec<u8>, PrivacyPassError> {
+    let origin_info = challenge.origin_info.join(",");
+    if challenge.issuer_name.is_empty() || challenge.issuer_name.len() > u16::MAX as usize {
+        return Err(PrivacyPassError::Malformed("issuer name".into()));
+    }
+    if !matches!(
+        challenge.redemption_context.len(),
+        0 | REDEMPTION_CONTEXT_LEN
+    ) {
+        return Err(PrivacyPassError::Malformed("redemption context".into()));
+    }
+    if origin_info.len() > u16::MAX as usize {
+        return Err(PrivacyPassError::Malformed("origin info".into()));
+    }
+
+    let mut encoded = Vec::new();
+    encoded.extend_from_slice(&challenge.token_type.to_be_bytes());
+    encoded.extend_from_slice(&(challenge.issuer_name.len() as u16).to_be_bytes());
+    encoded.extend_from_slice(challenge.issuer_name.as_bytes());
+    encoded.push(challenge.redemption_context.len() as u8);
+    encoded.extend_from_slice(&challenge.redemption_context);
+    encoded.extend_from_slice(&(origin_info.len() as u16).to_be_bytes());
+    encoded.extend_from_slice(origin_info.as_bytes());
+    Ok(encoded)
+}
+
+/// Parses a serialized token challenge.
+#[uniffi::export]
+pub fn decode_token_challenge(encoded: Vec<u8>) -> Result<TokenChallenge, PrivacyPassError> {
+    fn take<'a>(input: &mut &'a [u8], len: usize) -> Result<&'a [u8], PrivacyPassError> {
+        if input.len() < len {
+            return Err(PrivacyPassError::Malformed("token challenge".into()));
+        }
+        let (head, tail) = input.split_at(len);
+        *input = tail;
+        Ok(head)
+    }
+    fn take_u16(input: &mut &[u8]) -> Result<u16, PrivacyPassError> {
+        let bytes = take(input, 2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+    fn utf8(bytes: &[u8], name: &str) -> Result<String, PrivacyPassError> {
+        String::from_utf8(bytes.to_vec()).map_err(|_| PrivacyPassError::Malformed(name.into()))
+    }
+
+    let mut input = encoded.as_slice();
+    let token_type = take_u16(&mut input)?;
+    let issuer_len = take_u16(&mut input)? as usize;
+    let issuer_name = utf8(take(&mut input, issuer_len)?, "issuer name")?;
+    let context_len = take(&mut input, 1)?[0] as usize;
+    let redemption_context = take(&mut input, context_len)?.to_vec();
+    let origin_len = take_u16(&mut input)? as usize;
+    let origin_info = utf8(take(&mut input, origin_len)?, "origin info")?;
+    if !input.is_empty() {
+        return Err(PrivacyPassError::Malformed("token challenge".into()));
+    }
+
+    let challenge = TokenChallenge {
+        token_type,
+        issuer_name,
+        redemption_context,
+        origin_info: if origin_info.is_empty() {
+            Vec::new()
+        } else {
+            origin_info.split(',').map(str::to_string).collect()
+        },
+    };
+    // Re-encoding applies the same field checks as encoding.
+    encode_token_challenge(challenge.clone())?;
+    Ok(challenge)
+}
+
+/// Generates a new random issuer key.
+#[uniffi::export]
+pub fn privacy_pass_generate_issuer_key() -> Result<IssuerKey, PrivacyPassError> {
+    let private_key = NistP384::serialize_scalar(NistP384::random_scalar(&mut OsRng)).to_vec();
+    privacy_pass_issuer_key(private_key)
+}
+
+/// Recomputes the public key and token key ID of a stored issuer private key.
+#[uniffi::export]
+pub fn privacy_pass_issuer_key(private_key: Vec<u8>) -> Result<IssuerKey, PrivacyPassError> {
+    let server = VoprfServer::<NistP384>::new_with_key(&private_key)?;
+    let public_key = NistP384::serialize_elem(server.get_public_key()).to_vec();
+    Ok(IssuerKey {
+        token_key_id: token_key_id(&public_key),
+        private_key,
+        public_key,
+    })
+}
+
+/// Starts issuance of a token for `token_challenge` on the client.
+///
+/// `token_challenge` is the serialized challenge as received from the origin, and
+/// `issuer_public_key` the public key of the issuer named in it.
+#[uniffi::export]
+pub fn privacy_pass_token_request(
+    token_challenge: Vec<u8>,
+    issuer_public_key: Vec<u8>,
+) -> Result<TokenRequestResult, PrivacyPassError> {
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce);
+    let token_input = token_input(&token_challenge, &issuer_public_key, &nonce)?;
+    let blind = VoprfClient::<NistP384>::blind(&token_input, &mut OsRng)?;
+    Ok(build_token_request(
+        token_input,
+        &issuer_public_key,
+        &blind.state,
+        &blind.message,
+    ))
+}
+
+/// Turns the issuer's TokenResponse into a token, verifying the issuer's proof.
+#[uniffi::export]
+pub fn privacy_pass_finalize_token(
+    client_state: Vec<u8>,
+    token_response: Vec<u8>,
+) -> Result<Vec<u8>, PrivacyPassError> {
+    if client_state.len() != CLIENT_STATE_LEN {
+        return Err(PrivacyPassError::Malformed("client state".into()));
+    }
+    if token_response.len() != TOKEN_RESPONSE_LEN {
+        return Err(PrivacyPassError::Malformed("token response".into()));
+    }
+
+    let (token_input, rest) = client_state.split_at(TOKEN_INPUT_LEN);
+    let (public_key, client) = rest.split_at(NE);
+    let public_key = NistP384::deserialize_elem(public_key)?;
+    let client = VoprfClient::<NistP384>::deserialize(client)?;
+    let (evaluate_msg, evaluate_proof) = token_response.split_at(NE);
+    let evaluation_element = EvaluationElement::<NistP384>::deserialize(evaluate_msg)?;
+    let proof = Proof::<NistP384>::deserialize(evaluate_proof)?;
+
+    let authenticator = client
+        .finalize(token_input, &evaluation_element, &proof, public_key)
+        .map_err(|e| match e {
+            voprf::Error::ProofVerification => PrivacyPassError::InvalidToken,
+            e => e.into(),
+        })?;
+    Ok([token_input, authenticator.as_slice()].concat())
+}
+
+/// Private keys by token key ID.
+type IssuerKeys = HashMap<Vec<u8>, Vec<u8>>;
+
+/// Issues tokens and verifies their redemption, with a rotating set of issuer keys.
+#[derive(uniffi::Object)]
+pub struct TokenIssuer {
+    keys: Mutex<IssuerKeys>,
+    spent_tokens: Arc<dyn SpentTokenStore>,
+}
+
+#[uniffi::export]
+impl TokenIssuer {
+    /// Creates an issuer without keys, recording spent tokens in `spent_tokens`.
+    #[uniffi::constructor]
+    pub fn new(spent_tokens: Arc<dyn SpentTokenStore>) -> Arc<Self> {
+        Arc::new(Self {
+            keys: Mutex::new(HashMap::new()),
+            spent_tokens,
+        })
+    }
+
+    /// Adds an issuer key, returning its token key ID.
+    ///
+    /// Token requests only carry the last byte of the token key ID, so a key whose ID ends
+    /// in the same byte as a key already in use is rejected with `KeyIdCollision`.
+    pub fn add_key(&self, private_key: Vec<u8>) -> Result<Vec<u8>, PrivacyPassError> {
+        let key = privacy_pass_issuer_key(private_key)?;
+        let mut keys = self.lock()?;
+        if keys
+            .keys()
+            .any(|id| id != &key.token_key_id && id[NID - 1] == key.token_key_id[NID - 1])
+        {
+            return Err(PrivacyPassError::KeyIdCollision);
+        }
+        keys.insert(key.token_key_id.clone(), key.private_key);
+        Ok(key.token_key_id)
+    }
+
+    /// Removes the key with `token_key_id`, returning whether it was present.
+    ///
+    /// Tokens issued under a removed key can no longer be redeemed.
+    pub fn remove_key(&self, token_key_id: Vec<u8>) -> Result<bool, PrivacyPassError> {
+        Ok(self.lock()?.remove(&token_key_id).is_some())
+    }
+
+    /// Returns the token key IDs of the keys in use.
+    pub fn token_key_ids(&self) -> Result<Vec<Vec<u8>>, PrivacyPassError> {
+        Ok(self.lock()?.keys().cloned().collect())
+    }
+
+    /// Evaluates a TokenRequest, returning the TokenResponse.
+    pub fn issue(&self, token_request: Vec<u8>) -> Result<Vec<u8>, PrivacyPassError> {
+        if token_request.len() != TOKEN_REQUEST_LEN {
+            return Err(PrivacyPassError::Malformed("token request".into()));
+        }
+        check_token_type(&token_request)?;
+        let truncated_token_key_id = token_request[2];
+        let private_key = self
+            .lock()?
+            .iter()
+            .find(|(id, _)| id[NID - 1] == truncated_token_key_id)
+            .map(|(_, private_key)| private_key.clone())
+            .ok_or(PrivacyPassError::UnknownKey)?;
+
+        let blinded_element = BlindedElement::<NistP384>::deserialize(&token_request[3..])?;
+        let server = VoprfServer::<NistP384>::new_with_key(&private_key)?;
+        let result = server.blind_evaluate(&mut OsRng, &blinded_element);
+        Ok([
+            result.message.serialize().as_slice(),
+            result.proof.serialize().as_slice(),
+        ]
+        .concat())
+    }
+
+    /// Verifies a token presented for `token_challenge` and marks it as spent.
+    ///
+    /// Fails with `InvalidToken` if the token was not issued under one of the keys in use
+    /// for this challenge, and with `DoubleSpend` if it has been redeemed before.
+    pub fn redeem(&self, token: Vec<u8>, token_challenge: Vec<u8>) -> Result<(), PrivacyPassError> {
+        if token.len() != TOKEN_LEN {
+            return Err(PrivacyPassError::Malformed("token".into()));
+        }
+        check_token_type(&token)?;
+        let (token_input, authenticator) = token.split_at(TOKEN_INPUT_LEN);
+        let nonce = &token_input[2..2 + NONCE_LEN];
+        let challenge_digest = &token_input[2 + NONCE_LEN..2 + NONCE_LEN + DIGEST_LEN];
+        let token_key_id = &token_input[2 + NONCE_LEN + DIGEST_LEN..];
+
+        if challenge_digest != Sha256::digest(&token_challenge).as_slice() {
+            return Err(PrivacyPassError::InvalidToken);
+        }
+        let private_key = self
+            .lock()?
+            .get(token_key_id)
+            .cloned()
+            .ok_or(PrivacyPassError::UnknownKey)?;
+
+        let expected =
+            VoprfServer::<NistP384>::new_with_key(&private_key)?.evaluate(token_input)?;
+        if !bool::from(expected.as_slice().ct_eq(authenticator)) {
+            return Err(PrivacyPassError::InvalidToken);
+        }
+
+        // Only valid tokens reach the store, so forged tokens cannot fill it up.
+        if !self
+            .spent_tokens
+            .mark_spent(token_key_id.to_vec(), nonce.to_vec())?
+        {
+            return Err(PrivacyPassError::DoubleSpend);
+        }
+        Ok(())
+    }
+}
+
+impl TokenIssuer {
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, IssuerKeys>, PrivacyPassError> {
+        self.keys
+            .lock()
+            .map_err(|e| PrivacyPassError::Generic(format!("{:?}", e)))
+    }
+}
+
+fn token_key_id(public_key: &[u8]) -> Vec<u8> {
+    Sha256::digest(public_key).to_vec()
+}
+
+fn check_token_type(message: &[u8]) -> Result<(), PrivacyPassError> {
+    let token_type = u16::from_be_bytes([message[0], message[1]]);
+    if token_type != PRIVATE_TOKEN_TYPE {
+        return Err(PrivacyPassError::UnsupportedTokenType(token_type));
+    }
+    Ok(())
+}
+
+/// Builds the token input: token type, nonce, challenge digest and token key ID.
+fn token_input(
+    token_challenge: &[u8],
+    issuer_public_key: &[u8],
+    nonce: &[u8; NONCE_LEN],
+) -> Result<Vec<u8>, PrivacyPassError> {
+    let challenge = decode_token_challenge(token_challenge.to_vec())?;
+    if challenge.token_type != PRIVATE_TOKEN_TYPE {
+        return Err(PrivacyPassError::UnsupportedTokenType(challenge.token_type));
+    }
+    NistP384::deserialize_elem(issuer_public_key)?;
+
+    let mut token_input = Vec::with_capacity(TOKEN_INPUT_LEN);
+    token_input.extend_from_slice(&PRIVATE_TOKEN_TYPE.to_be_bytes());
+    token_input.extend_from_slice(nonce);
+    token_input.extend_from_slice(&Sha256::digest(token_challenge));
+    token_input.extend_from_slice(&token_key_id(issuer_public_key));
+    Ok(token_input)
+}
+
+fn build_token_request(
+    token_input: Vec<u8>,
+    issuer_public_key: &[u8],
+    client: &VoprfClient<NistP384>,
+    blinded_element: &BlindedElement<NistP384>,
+) -> TokenRequestResult {
+    let mut token_request = Vec::with_capacity(TOKEN_REQUEST_LEN);
+    token_request.extend_from_slice(&PRIVATE_TOKEN_TYPE.to_be_bytes());
+    token_request.push(token_input[TOKEN_INPUT_LEN - 1]);
+    token_request.extend_from_slice(&blinded_element.serialize());
+
+    let client_state = [
+        token_input.as_slice(),
+        issuer_public_key,
+        client.serialize().as_slice(),
+    ]
+    .concat();
+    TokenRequestResult {
+        token_request,
+        client_state,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The first P-384 vector of RFC 9578 Appendix A.1.
+    mod vector {
+        pub const SK_S: &str = "39b0d04d3732459288fc5edb89bb02c2aa42e06709f201d6c518871d518114910bee3c919bed1bbffe3fc1b87d53240a";
+        pub const PK_S: &str = "02d45bf522425cdd2227d3f27d245d9d563008829252172d34e48469290c21da1a46d42ca38f7beabdf05c074aee1455bf";
+        pub const TOKEN_CHALLENGE: &str = "0001000e6973737565722e6578616d706c65205de58a52fcdaef25ca3f65448d04e040fb1924e8264acfccfc6c5ad451d582b3000e6f726967696e2e6578616d706c65";
+        pub const NONCE: &str = "ccfd6265c63b03e5e5c968a87eb6a598e1a98ecce5682e81dd3853d8178c06b4";
+        pub const BLIND: &str = "e1797b2f73f3ad79f7c5f752adb77fe638f8ce86fdb074657847474af2bc3a893a1e735944b4f98eb77edbe5cbb0d4b8";
+        pub const TOKEN_REQUEST: &str = "0001f40380ccc863f840e25de6fa3dd13b310718db08adde13a7c9e43610ec424c070a1ef91496d4e0d4ae077023ab0ca8147b09";
+        pub const TOKEN_RESPONSE: &str = "03af73d07367f721bacbf2d1bb0568a5bff892356ab637bcbe057a15a75ee5b132e227ab3c1f30b2825e0edbb87782ab7f21078b0634b630441925162597d9a8b1306aac69604ebc1e6620d3526134515fc511e1c5143bcac035fbe7efff09a8cc7bcf02ef380c8771ebc925ac56c9a126d23fd8f7ef3308e12e2eb7959cd95e0530e1e6316c86344856f23301795d0af3";
+        pub const TOKEN: &str = "0001ccfd6265c63b03e5e5c968a87eb6a598e1a98ecce5682e81dd3853d8178c06b4501370b494089dc462802af545e63809581ee6ef57890a12105c28368169514bf260d0792bf7f46c9866a6d37c3032d8714415f87f5f6903d7fb071e253be2f4c0fee46fa639b6ddf7ad1aab35937c4fed19059c5328930433b1e21884b54262751b17c56aabbaebc71ce69ae4acb9cd";
+    }
+
+    fn challenge() -> Vec<u8> {
+        encode_token_challenge(TokenChallenge {
+            token_type: PRIVATE_TOKEN_TYPE,
+            issuer_name: "issuer.example".into(),
+            redemption_context: Vec::new(),
+            origin_info: vec!["origin.example".into()],
+        })
+        .unwrap()
+    }
+
+    fn issue_token(issuer: &TokenIssuer, public_key: &[u8], challenge: &[u8]) -> Vec<u8> {
+        let request = privacy_pass_token_request(challenge.to_vec(), public_key.to_vec()).unwrap();
+        let response = issuer.issue(request.token_request).unwrap();
+        privacy_pass_finalize_token(request.client_state, response).unwrap()
+    }
+
+    #[test]
+    fn rfc9578_vector() {
+        let sk = hex::decode(vector::SK_S).unwrap();
+        let pk = hex::decode(vector::PK_S).unwrap();
+        let challenge = hex::decode(vector::TOKEN_CHALLENGE).unwrap();
+        assert_eq!(privacy_pass_issuer_key(sk.clone()).unwrap().public_key, pk);
+
+        // The blinded element for the vector's blind, as `VoprfClient::blind` would compute it.
+        let nonce: [u8; NONCE_LEN] = hex::decode(vector::NONCE).unwrap().try_into().unwrap();
+        let token_input = token_input(&challenge, &pk, &nonce).unwrap();
+        let blind = NistP384::deserialize_scalar(&hex::decode(vector::BLIND).unwrap()).unwrap();
+        let point = NistP384::hash_to_curve::<sha2::Sha384>(
+            &[&token_input],
+            &[b"HashToGroup-", b"OPRFV1-\x01-P384-SHA384"],
+        )
+        .unwrap();
+        let client_bytes = [
+            NistP384::serialize_scalar(blind).as_slice(),
+            NistP384::serialize_elem(point * blind).as_slice(),
+        ]
+        .concat();
+        let client = VoprfClient::<NistP384>::deserialize(&client_bytes).unwrap();
+        let blinded_element = BlindedElement::<NistP384>::deserialize(&client_bytes[NS..]).unwrap();
+
+        let request = build_token_request(token_input, &pk, &client, &blinded_element);
+        assert_eq!(hex::encode(&request.token_request), vector::TOKEN_REQUEST);
+
+        let issuer = TokenIssuer::new(memory_spent_token_store());
+        issuer.add_key(sk).unwrap();
+        let response = issuer.issue(request.token_request).unwrap();
+        assert_eq!(
+            hex::encode(&response[..NE]),
+            &vector::TOKEN_RESPONSE[..2 * NE]
+        );
+
+        let token = privacy_pass_finalize_token(
+            request.client_state,
+            hex::decode(vector::TOKEN_RESPONSE).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(hex::encode(&token), vector::TOKEN);
+        issuer.redeem(token, challenge).unwrap();
+    }
+
+    #[test]
+    fn token_is_redeemed_once() {
+        let key = privacy_pass_generate_issuer_key().unwrap();
+        let issuer = TokenIssuer::new(memory_spent_token_store());
+        issuer.add_key(key.private_key).unwrap();
+
+        let challenge = challenge();
+        let token = issue_token(&issuer, &key.public_key, &challenge);
+        issuer.redeem(token.clone(), challenge.clone()).unwrap();
+        assert!(matches!(
+            issuer.redeem(token, challenge),
+            Err(PrivacyPassError::DoubleSpend)
+        ));
+    }
+
+    #[test]
+    fn forged_or_misdirected_token_is_rejected() {
+        let key = privacy_pass_generate_issuer_key().unwrap();
+        let issuer = TokenIssuer::new(memory_spent_token_store());
+        issuer.add_key(key.private_key).unwrap();
+
+        let challenge = challenge();
+        let mut token = issue_token(&issuer, &key.public_key, &challenge);
+
+        let other_challenge = encode_token_challenge(TokenChallenge {
+            token_type: PRIVATE_TOKEN_TYPE,
+            issuer_name: "issuer.example".into(),
+            redemption_context: vec![7; 32],
+            origin_info: Vec::new(),
+        })
+        .unwrap();
+        assert!(matches!(
+            issuer.redeem(token.clone(), other_challenge),
+            Err(PrivacyPassError::InvalidToken)
+        ));
+
+        token[TOKEN_LEN - 1] ^= 1;
+        assert!(matches!(
+            issuer.redeem(token, challenge),
+            Err(PrivacyPassError::InvalidToken)
+        ));
+    }
+
+    #[test]
+    fn removed_key_no_longer_issues_or_redeems() {
+        let key = privacy_pass_generate_issuer_key().unwrap();
+        let issuer = TokenIssuer::new(memory_spent_token_store());
+        let token_key_id = issuer.add_key(key.private_key).unwrap();
+        assert_eq!(token_key_id, key.token_key_id);
+
+        let challenge = challenge();
+        let token = issue_token(&issuer, &key.public_key, &challenge);
+        assert!(issuer.remove_key(token_key_id).unwrap());
+
+        let request = privacy_pass_token_request(challenge.clone(), key.public_key).unwrap();
+        assert!(matches!(
+            issuer.issue(request.token_request),
+            Err(PrivacyPassError::UnknownKey)
+        ));
+        assert!(matches!(
+            issuer.redeem(token, challenge),
+            Err(PrivacyPassError::UnknownKey)
+        ));
+    }
+
+    #[test]
+    fn token_challenge_round_trip() {
+        let challenge = TokenChallenge {
+            token_type: PRIVATE_TOKEN_TYPE,
+            issuer_name: "issuer.example".into(),
+            redemption_context: vec![1; 32],
+            origin_info: vec!["a.example".into(), "b.example".into()],
+        };
+        let encoded = encode_token_challenge(challenge.clone()).unwrap();
+        assert_eq!(decode_token_challenge(encoded).unwrap(), challenge);
+
+        let vector = hex::decode(vector::TOKEN_CHALLENGE).unwrap();
+        let decoded = decode_token_challenge(vector.clone()).unwrap();
+        assert_eq!(decoded.issuer_name, "issuer.example");
+        assert_eq!(encode_token_challenge(decoded).unwrap(), vector);
+    }
+}
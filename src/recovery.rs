@@ -0,0 +1,213 @@
+// SPDX-FileCopyrightText: 2026 Digg - Agency for Digital Government
+//
+// SPDX-License-Identifier: EUPL-1.2
+
+//! This module generates recovery codes, registered as secondary OPAQUE credentials of an
+//! account so a user who forgot their password can still log in once per code.
+//!
+//! A recovery code is five groups of five Crockford base32 characters. The first group is
+//! the code ID, from which, together with the account's credential identifier, the
+//! credential identifier of the code is derived; the other four groups are 100 random bits
+//! used as the OPAQUE password. Each code is registered with `client_registration_start`,
+//! `server_registration_start` under its derived identifier and
+//! `client_registration_finish`, and the client wraps the account secret under the export
+//! key of each registration with `enrolment_wrap_account_secret`. A recovery login with
+//! the code then unwraps the account secret with `enrolment_unwrap_account_secret`.
+//!
+//! The server enforces that each code is used once: after `server_login_finish` succeeds
+//! for a recovery credential, it deletes the password file and only completes the recovery
+//! if it was the one to delete it (see `SqliteCredentialStore::consume`).
+
+use rand::{rngs::OsRng, RngCore};
+use sha2::{Digest, Sha256};
+
+/// Prefix of the hash input of a recovery credential identifier.
+const RECOVERY_CREDENTIAL_TAG: &[u8] = b"OPAQUE-Recovery-Credential-v1";
+/// The Crockford base32 alphabet.
+const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+const GROUP_LEN: usize = 5;
+const GROUP_COUNT: usize = 5;
+/// The most codes generated at once.
+const MAX_CODES: u16 = 32;
+
+#[derive(Debug, thiserror::Error, uniffi::Error)]
+pub enum RecoveryError {
+    #[error("Invalid parameter: {0}")]
+    InvalidParameter(String),
+    #[error("Invalid recovery code: {0}")]
+    InvalidCode(String),
+}
+
+#[derive(Clone, PartialEq, Eq, uniffi::Record)]
+pub struct RecoveryCode {
+    /// The code as shown to the user, e.g. `7K3QD-...`, in five dash-separated groups.
+    pub code: String,
+    /// The credential identifier the code is registered and logged in under.
+    pub credential_identifier: Vec<u8>,
+    /// The password passed to the OPAQUE registration and login functions.
+    pub password: Vec<u8>,
+}
+
+/// Derives the credential identifier of a code ID.
+fn credential_identifier(
+    account_credential_identifier: &[u8],
+    code_id: &[u8],
+) -> Result<Vec<u8>, RecoveryError> {
+    let len = u16::try_from(account_credential_identifier.len()).map_err(|_| {
+        RecoveryError::InvalidParameter(
+            "the credential identifier is longer than 65535 bytes".into(),
+        )
+    })?;
+    let mut hasher = Sha256::new();
+    hasher.update(RECOVERY_CREDENTIAL_TAG);
+    hasher.update(len.to_be_bytes());
+    hasher.update(account_credential_identifier);
+    hasher.update(code_id);
+    Ok(hasher.finalize().to_vec())
+}
+
+/// Builds the recovery code of its normalized characters.
+fn recovery_code(
+    account_credential_identifier: &[u8],
+    chars: &[u8],
+) -> Result<RecoveryCode, RecoveryError> {
+    let (code_id, secret) = chars.split_at(GROUP_LEN);
+    let code = chars
+        .chunks(GROUP_LEN)
+        .map(|group| String::from_utf8_lossy(group).into_owned())
+        .collect::<Vec<_>>()
+        .join("-");
+
+    Ok(RecoveryCode {
+        code,
+        credential_identifier: credential_identifier(account_credential_identifier, code_id)?,
+        password: secret.to_vec(),
+    })
+}
+
+/// Generates `count` recovery codes, between 1 and 32, for the account with
+/// `account_credential_identifier`.
+///
+/// The codes have distinct code IDs and so distinct credential identifiers. They are shown
+/// to the user once; only their registrations are kept.
+#[uniffi::export]
+pub fn recovery_generate_codes(
+    account_credential_identifier: Vec<u8>,
+    count: u16,
+) -> Result<Vec<RecoveryCode>, RecoveryError> {
+    if count == 0 || count > MAX_CODES {
+        return Err(RecoveryError::InvalidParameter(format!(
+            "the number of codes must be between 1 and {}",
+            MAX_CODES
+        )));
+    }
+
+    let mut codes: Vec<RecoveryCode> = Vec::new();
+    while codes.len() < count as usize {
+        // The alphabet has 32 characters, so the low 5 bits give an unbiased character.
+        let chars: Vec<u8> = (0..GROUP_LEN * GROUP_COUNT)
+            .map(|_| ALPHABET[(OsRng.next_u32() & 31) as usize])
+            .collect();
+        let code = recovery_code(&account_credential_identifier, &chars)?;
+        if codes
+            .iter()
+            .all(|c| c.credential_identifier != code.credential_identifier)
+        {
+            codes.push(code);
+        }
+    }
+    Ok(codes)
+}
+
+/// Parses a recovery code entered by the user for the account with
+/// `account_credential_identifier`, returning the credential identifier and password to
+/// log in with.
+///
+/// Input is case-insensitive and dashes and whitespace are ignored; as in Crockford base32,
+/// `O` is read as `0` and `I` and `L` as `1`.
+#[uniffi::export]
+pub fn recovery_parse_code(
+    account_credential_identifier: Vec<u8>,
+    code: String,
+) -> Result<RecoveryCode, RecoveryError> {
+    let mut chars = Vec::with_capacity(GROUP_LEN * GROUP_COUNT);
+    for c in code.chars() {
+        let c = match c.to_ascii_uppercase() {
+            '-' => continue,
+            c if c.is_whitespace() => continue,
+            'O' => '0',
+            'I' | 'L' => '1',
+            c => c,
+        };
+        if !c.is_ascii() || !ALPHABET.contains(&(c as u8)) {
+            return Err(RecoveryError::InvalidCode(format!(
+                "'{}' is not a recovery code character",
+                c
+            )));
+        }
+        chars.push(c as u8);
+    }
+    if chars.len() != GROUP_LEN * GROUP_COUNT {
+        return Err(RecoveryError::InvalidCode(format!(
+            "a recovery code has {} characters, not {}",
+            GROUP_LEN * GROUP_COUNT,
+            chars.len()
+        )));
+    }
+
+    recovery_code(&account_credential_identifier, &chars)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_codes_parse_to_themselves() {
+        let codes = recovery_generate_codes(b"alice".to_vec(), 10).unwrap();
+        assert_eq!(codes.len(), 10);
+        for code in &codes {
+            assert_eq!(code.code.len(), 29);
+            assert_eq!(code.password.len(), 20);
+            assert_eq!(code.credential_identifier.len(), 32);
+            assert!(recovery_parse_code(b"alice".to_vec(), code.code.clone()).unwrap() == *code);
+        }
+        for (i, a) in codes.iter().enumerate() {
+            for b in &codes[i + 1..] {
+                assert_ne!(a.credential_identifier, b.credential_identifier);
+            }
+        }
+    }
+
+    #[test]
+    fn test_parsing_is_lenient() {
+        let code =
+            recovery_parse_code(b"alice".to_vec(), "0123K-ABCDE-11111-VWXYZ-00000".into()).unwrap();
+        let lenient =
+            recovery_parse_code(b"alice".to_vec(), " o123k abcde IiLl1 vwxyz OoOoO ".into())
+                .unwrap();
+        assert!(lenient == code);
+        assert_eq!(code.password, b"ABCDE11111VWXYZ00000");
+
+        // The identifier depends on the account
+        let other = recovery_parse_code(b"bob".to_vec(), code.code.clone()).unwrap();
+        assert_ne!(other.credential_identifier, code.credential_identifier);
+    }
+
+    #[test]
+    fn test_invalid_input_is_rejected() {
+        for code in [
+            "0123K-ABCDE-11111-VWXYZ-0000",
+            "0123K-ABCDE-11111-VWXYZ-000000",
+            "0123K-ABCDE-11111-VWXYZ-0000U",
+            "0123K-ABCDE-11111-VWXYZ-0000é",
+        ] {
+            assert!(matches!(
+                recovery_parse_code(b"alice".to_vec(), code.into()),
+                Err(RecoveryError::InvalidCode(_))
+            ));
+        }
+        assert!(recovery_generate_codes(b"alice".to_vec(), 0).is_err());
+        assert!(recovery_generate_codes(b"alice".to_vec(), 33).is_err());
+    }
+}
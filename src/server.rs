@@ -2,7 +2,7 @@
 //
 // SPDX-License-Identifier: EUPL-1.2
 
-use crate::common::DefaultCipherSuite;
+use crate::common::{DefaultCipherSuite, ServerSetupError};
 use opaque_ke::ServerSetup;
 use rand::rngs::OsRng;
 
@@ -13,3 +13,15 @@ pub fn server_setup() -> Vec<u8> {
     let server_setup = ServerSetup::<DefaultCipherSuite>::new(&mut rng);
     server_setup.serialize().to_vec()
 }
+
+/// Returns the server's static public key from a server setup.
+///
+/// This is the key clients see as the server's identity when no explicit
+/// server identifier is used.
+#[uniffi::export]
+pub fn server_public_key(server_setup: Vec<u8>) -> Result<Vec<u8>, ServerSetupError> {
+    let server_setup_obj = ServerSetup::<DefaultCipherSuite>::deserialize(&server_setup)
+        .map_err(|e| ServerSetupError::Generic(format!("{:?}", e)))?;
+
+    Ok(server_setup_obj.keypair().public().serialize().to_vec())
+}
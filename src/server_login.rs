@@ -3,13 +3,55 @@
 // SPDX-License-Identifier: EUPL-1.2
 
 //! This module handles the server-side of the OPAQUE login process.
+//!
+//! The server login state is
+//! `version (1) || opaque-ke ServerLogin state || binding hash (32)`. Version 1
+//! replaced the bare opaque-ke state, so states from `server_login_start` of earlier
+//! releases are rejected by `server_login_finish` and their logins must be restarted.
+//!
+//! opaque-ke fixes the context and identifiers of a login in `ServerLogin::start` and
+//! ignores those given to `ServerLogin::finish`. The binding hash is SHA-256 of the
+//! context and the identity mode, each length-prefixed, so `server_login_finish` can
+//! require the same ones.
 
-use crate::common::{DefaultCipherSuite, LoginError};
+use crate::common::{push_length_prefixed, DefaultCipherSuite, IdentityMode, LoginError};
 use opaque_ke::{
-    CredentialFinalization, CredentialRequest, Identifiers, ServerLogin, ServerLoginParameters,
+    CredentialFinalization, CredentialRequest, ServerLogin, ServerLoginParameters,
     ServerRegistration, ServerSetup,
 };
 use rand::rngs::OsRng;
+use sha2::{Digest, Sha256};
+
+/// Version of the server login state format, its first byte.
+const SERVER_LOGIN_STATE_VERSION: u8 = 1;
+/// Length of the binding hash appended to the serialized server login state.
+const BINDING_HASH_LEN: usize = 32;
+
+/// opaque-ke fixes the context and identifiers of a login in `server_login_start` and
+/// ignores those given to `server_login_finish`, so the state records a hash of them to
+/// check them against.
+///
+/// The hash input is the length-prefixed context, then `0` for `DefaultPublicKeys` or `1`
+/// and the length-prefixed client and server identities for `Explicit`.
+fn binding_hash(
+    context: Option<&[u8]>,
+    identity_mode: &IdentityMode,
+) -> Result<[u8; BINDING_HASH_LEN], LoginError> {
+    let mut input = Vec::new();
+    push_length_prefixed(&mut input, "the context", context.unwrap_or_default())
+        .map_err(LoginError::Generic)?;
+    match identity_mode {
+        IdentityMode::DefaultPublicKeys => input.push(0),
+        IdentityMode::Explicit { client, server } => {
+            input.push(1);
+            push_length_prefixed(&mut input, "the client identity", client)
+                .map_err(LoginError::Generic)?;
+            push_length_prefixed(&mut input, "the server identity", server)
+                .map_err(LoginError::Generic)?;
+        }
+    }
+    Ok(Sha256::digest(&input).into())
+}
 
 #[derive(uniffi::Record)]
 pub struct ServerLoginStartResult {
@@ -23,9 +65,10 @@ pub struct ServerLoginStartResult {
 /// the user's password file (from registration),
 /// a credential request from the client,
 /// a credential identifier to look up the user,
-/// and optional parameters for context and identifiers.
+/// an optional context and the identity mode used at registration.
 /// It returns a credential response to be sent to the client,
-/// and a server login state to be used in the finish step.
+/// and a server login state to be used in the finish step,
+/// which records the context and identity mode so the finish step can require the same ones.
 #[uniffi::export]
 pub fn server_login_start(
     server_setup: Vec<u8>,
@@ -33,28 +76,23 @@ pub fn server_login_start(
     credential_request: Vec<u8>,
     credential_identifier: &[u8],
     context: Option<Vec<u8>>,
-    client_identifier: Option<Vec<u8>>,
-    server_identifier: Option<Vec<u8>>,
+    identity_mode: IdentityMode,
 ) -> Result<ServerLoginStartResult, LoginError> {
     let mut rng = OsRng;
 
     let server_setup_obj = ServerSetup::<DefaultCipherSuite>::deserialize(&server_setup)
         .map_err(|e| LoginError::Generic(format!("{:?}", e)))?;
 
-    let password_opt = match ServerRegistration::<DefaultCipherSuite>::deserialize(&password_file) {
-        Ok(r) => Some(r),
-        Err(_) => None,
-    };
+    let binding = binding_hash(context.as_deref(), &identity_mode)?;
+
+    let password_opt = ServerRegistration::<DefaultCipherSuite>::deserialize(&password_file).ok();
 
     let credential_req = CredentialRequest::<DefaultCipherSuite>::deserialize(&credential_request)
         .map_err(|e| LoginError::Generic(format!("{:?}", e)))?;
 
     let params = ServerLoginParameters {
         context: context.as_deref(),
-        identifiers: Identifiers {
-            client: client_identifier.as_deref(),
-            server: server_identifier.as_deref(),
-        },
+        identifiers: identity_mode.identifiers(),
     };
 
     let login_result = ServerLogin::start(
@@ -67,9 +105,13 @@ pub fn server_login_start(
     )
     .map_err(|e| LoginError::Generic(format!("{:?}", e)))?;
 
+    let mut server_login = vec![SERVER_LOGIN_STATE_VERSION];
+    server_login.extend_from_slice(&login_result.state.serialize());
+    server_login.extend_from_slice(&binding);
+
     Ok(ServerLoginStartResult {
         credential_response: login_result.message.serialize().to_vec(),
-        server_login: login_result.state.serialize().to_vec(),
+        server_login,
     })
 }
 
@@ -77,17 +119,37 @@ pub fn server_login_start(
 ///
 /// This function takes the server's login state from the start step,
 /// the client's credential finalization message,
-/// and optional parameters for context and identifiers.
+/// an optional context and the identity mode.
+/// The context and identity mode must be the ones given to `server_login_start`.
 /// It returns the session key on successful authentication.
 #[uniffi::export]
 pub fn server_login_finish(
     server_login: Vec<u8>,
     credential_finalization: Vec<u8>,
     context: Option<Vec<u8>>,
-    client_identifier: Option<Vec<u8>>,
-    server_identifier: Option<Vec<u8>>,
+    identity_mode: IdentityMode,
 ) -> Result<Vec<u8>, LoginError> {
-    let server_login_obj = ServerLogin::<DefaultCipherSuite>::deserialize(&server_login)
+    let (version, state) = server_login
+        .split_first()
+        .ok_or_else(|| LoginError::Generic("server login state is empty".into()))?;
+    if *version != SERVER_LOGIN_STATE_VERSION {
+        return Err(LoginError::Generic(format!(
+            "unsupported server login state version {}",
+            version
+        )));
+    }
+    let state_len = state
+        .len()
+        .checked_sub(BINDING_HASH_LEN)
+        .ok_or_else(|| LoginError::Generic("server login state is too short".into()))?;
+    let (state, expected_binding_hash) = state.split_at(state_len);
+    if binding_hash(context.as_deref(), &identity_mode)? != expected_binding_hash {
+        return Err(LoginError::Generic(
+            "context or identity mode differs from the one the login was started with".into(),
+        ));
+    }
+
+    let server_login_obj = ServerLogin::<DefaultCipherSuite>::deserialize(state)
         .map_err(|e| LoginError::Generic(format!("{:?}", e)))?;
 
     let credential_finalization_obj =
@@ -96,10 +158,7 @@ pub fn server_login_finish(
 
     let params = ServerLoginParameters {
         context: context.as_deref(),
-        identifiers: Identifiers {
-            client: client_identifier.as_deref(),
-            server: server_identifier.as_deref(),
-        },
+        identifiers: identity_mode.identifiers(),
     };
 
     let result = server_login_obj
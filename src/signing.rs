@@ -0,0 +1,257 @@
+// SPDX-FileCopyrightText: 2026 Digg - Agency for Digital Government
+//
+// SPDX-License-Identifier: EUPL-1.2
+
+//! This module derives account-level signing keys from the OPAQUE export key.
+//!
+//! The export key returned by `client_registration_finish` and `client_login_finish` is
+//! the same on every login with the same password file, so a key derived from it is
+//! recreated on every login and never has to be stored on the device. The server never
+//! learns the export key, so it can only verify signatures, with the public key it is
+//! given at registration.
+
+use crate::group::{group_scalar_reduce, GroupKind};
+use ed25519_dalek::{Signer, Verifier};
+use hkdf::Hkdf;
+use sha2::Sha256;
+
+/// Prefix of the HKDF info of every derived signing key.
+const SIGNING_KEY_INFO: &[u8] = b"OPAQUE-ExportKey-SigningKey";
+
+#[derive(Debug, thiserror::Error, uniffi::Error)]
+pub enum SigningError {
+    #[error("Signing failed: {0}")]
+    Generic(String),
+    #[error("Invalid public key")]
+    InvalidPublicKey,
+    #[error("Invalid parameter: {0}")]
+    InvalidParameter(String),
+}
+
+/// The signature algorithms a key can be derived for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum SigningAlgorithm {
+    /// Ed25519 (RFC 8032), with 32-byte public keys and 64-byte signatures.
+    Ed25519,
+    /// ECDSA over P-256 with SHA-256 and RFC 6979 nonces, with compressed SEC1 public keys
+    /// and 64-byte `r || s` signatures.
+    EcdsaP256Sha256,
+}
+
+impl SigningAlgorithm {
+    fn label(self) -> &'static [u8] {
+        match self {
+            SigningAlgorithm::Ed25519 => b"Ed25519",
+            SigningAlgorithm::EcdsaP256Sha256 => b"ECDSA-P256-SHA256",
+        }
+    }
+}
+
+enum SigningKey {
+    Ed25519(ed25519_dalek::SigningKey),
+    EcdsaP256(p256::ecdsa::SigningKey),
+}
+
+/// Derives the signing key of `algorithm` and `key_info` from an export key.
+fn derive_signing_key(
+    algorithm: SigningAlgorithm,
+    export_key: &[u8],
+    key_info: &[u8],
+) -> Result<SigningKey, SigningError> {
+    let hkdf = Hkdf::<Sha256>::from_prk(export_key).map_err(|_| {
+        SigningError::InvalidParameter("the export key must be at least 32 bytes".into())
+    })?;
+    let key_info_len = u16::try_from(key_info.len())
+        .map_err(|_| SigningError::InvalidParameter("the key info is too long".into()))?;
+    let info: [&[u8]; 4] = [
+        SIGNING_KEY_INFO,
+        algorithm.label(),
+        &key_info_len.to_be_bytes(),
+        key_info,
+    ];
+    let err = |e| SigningError::Generic(format!("{:?}", e));
+
+    match algorithm {
+        SigningAlgorithm::Ed25519 => {
+            let mut seed = [0u8; 32];
+            hkdf.expand_multi_info(&info, &mut seed).map_err(err)?;
+            let key = ed25519_dalek::SigningKey::from_bytes(&seed);
+            Ok(SigningKey::Ed25519(key))
+        }
+        SigningAlgorithm::EcdsaP256Sha256 => {
+            // 16 bytes more than the scalar, so reducing them is unbiased.
+            let mut wide = [0u8; 48];
+            hkdf.expand_multi_info(&info, &mut wide).map_err(err)?;
+            let scalar = group_scalar_reduce(GroupKind::P256, wide.to_vec())
+                .map_err(|e| SigningError::Generic(format!("{:?}", e)))?;
+            p256::ecdsa::SigningKey::from_slice(&scalar)
+                .map(SigningKey::EcdsaP256)
+                .map_err(|e| SigningError::Generic(format!("{:?}", e)))
+        }
+    }
+}
+
+/// Returns the public key of the signing key derived from `export_key`.
+///
+/// `key_info` separates keys for different purposes, e.g. `recovery`; the same export key,
+/// algorithm and key info always give the same key.
+#[uniffi::export]
+pub fn signing_public_key(
+    algorithm: SigningAlgorithm,
+    export_key: Vec<u8>,
+    key_info: Vec<u8>,
+) -> Result<Vec<u8>, SigningError> {
+    let public_key = match derive_signing_key(algorithm, &export_key, &key_info)? {
+        SigningKey::Ed25519(key) => key.verifying_key().to_bytes().to_vec(),
+        SigningKey::EcdsaP256(key) => key
+            .verifying_key()
+            .to_encoded_point(true)
+            .as_bytes()
+            .to_vec(),
+    };
+    Ok(public_key)
+}
+
+/// Signs `message` with the signing key derived from `export_key`.
+///
+/// Signing is deterministic for both algorithms.
+#[uniffi::export]
+pub fn signing_sign(
+    algorithm: SigningAlgorithm,
+    export_key: Vec<u8>,
+    key_info: Vec<u8>,
+    message: Vec<u8>,
+) -> Result<Vec<u8>, SigningError> {
+    let signature = match derive_signing_key(algorithm, &export_key, &key_info)? {
+        SigningKey::Ed25519(key) => key.sign(&message).to_bytes().to_vec(),
+        SigningKey::EcdsaP256(key) => {
+            let signature: p256::ecdsa::Signature = key.sign(&message);
+            signature.to_bytes().to_vec()
+        }
+    };
+    Ok(signature)
+}
+
+/// Verifies a signature with a public key from [`signing_public_key`].
+///
+/// Returns whether the signature is valid; a malformed signature is not valid, while a
+/// malformed public key is an error.
+#[uniffi::export]
+pub fn signing_verify(
+    algorithm: SigningAlgorithm,
+    public_key: Vec<u8>,
+    message: Vec<u8>,
+    signature: Vec<u8>,
+) -> Result<bool, SigningError> {
+    match algorithm {
+        SigningAlgorithm::Ed25519 => {
+            let public_key = <[u8; 32]>::try_from(public_key.as_slice())
+                .ok()
+                .and_then(|bytes| ed25519_dalek::VerifyingKey::from_bytes(&bytes).ok())
+                .ok_or(SigningError::InvalidPublicKey)?;
+            Ok(ed25519_dalek::Signature::from_slice(&signature)
+                .is_ok_and(|signature| public_key.verify_strict(&message, &signature).is_ok()))
+        }
+        SigningAlgorithm::EcdsaP256Sha256 => {
+            let public_key = p256::ecdsa::VerifyingKey::from_sec1_bytes(&public_key)
+                .map_err(|_| SigningError::InvalidPublicKey)?;
+            Ok(p256::ecdsa::Signature::from_slice(&signature)
+                .is_ok_and(|signature| public_key.verify(&message, &signature).is_ok()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALGORITHMS: [SigningAlgorithm; 2] =
+        [SigningAlgorithm::Ed25519, SigningAlgorithm::EcdsaP256Sha256];
+
+    #[test]
+    fn test_sign_and_verify() {
+        let export_key = vec![7u8; 32];
+        for algorithm in ALGORITHMS {
+            let public_key =
+                signing_public_key(algorithm, export_key.clone(), b"recovery".to_vec()).unwrap();
+            let signature = signing_sign(
+                algorithm,
+                export_key.clone(),
+                b"recovery".to_vec(),
+                b"message".to_vec(),
+            )
+            .unwrap();
+            assert_eq!(signature.len(), 64);
+            assert!(signing_verify(
+                algorithm,
+                public_key.clone(),
+                b"message".to_vec(),
+                signature.clone()
+            )
+            .unwrap());
+
+            assert!(!signing_verify(
+                algorithm,
+                public_key.clone(),
+                b"other message".to_vec(),
+                signature.clone()
+            )
+            .unwrap());
+            let mut tampered = signature.clone();
+            tampered[10] ^= 1;
+            assert!(
+                !signing_verify(algorithm, public_key.clone(), b"message".to_vec(), tampered)
+                    .unwrap()
+            );
+            assert!(!signing_verify(
+                algorithm,
+                public_key,
+                b"message".to_vec(),
+                signature[..63].to_vec()
+            )
+            .unwrap());
+        }
+    }
+
+    #[test]
+    fn test_keys_are_deterministic_and_separated() {
+        for algorithm in ALGORITHMS {
+            let key = |export_key: &[u8], key_info: &[u8]| {
+                signing_public_key(algorithm, export_key.to_vec(), key_info.to_vec()).unwrap()
+            };
+            assert_eq!(key(&[1; 32], b"a"), key(&[1; 32], b"a"));
+            assert_ne!(key(&[1; 32], b"a"), key(&[2; 32], b"a"));
+            assert_ne!(key(&[1; 32], b"a"), key(&[1; 32], b"b"));
+
+            let sign =
+                || signing_sign(algorithm, vec![1; 32], b"a".to_vec(), b"m".to_vec()).unwrap();
+            assert_eq!(sign(), sign());
+        }
+        assert_eq!(
+            signing_public_key(SigningAlgorithm::Ed25519, vec![1; 32], vec![])
+                .unwrap()
+                .len(),
+            32
+        );
+        assert_eq!(
+            signing_public_key(SigningAlgorithm::EcdsaP256Sha256, vec![1; 32], vec![])
+                .unwrap()
+                .len(),
+            33
+        );
+    }
+
+    #[test]
+    fn test_invalid_inputs_are_rejected() {
+        for algorithm in ALGORITHMS {
+            assert!(matches!(
+                signing_public_key(algorithm, vec![1; 16], vec![]),
+                Err(SigningError::InvalidParameter(_))
+            ));
+            assert!(matches!(
+                signing_verify(algorithm, vec![0xff; 33], vec![], vec![0; 64]),
+                Err(SigningError::InvalidPublicKey)
+            ));
+        }
+    }
+}
@@ -0,0 +1,383 @@
+// SPDX-FileCopyrightText: 2026 Digg - Agency for Digital Government
+//
+// SPDX-License-Identifier: EUPL-1.2
+
+//! This module provides a SQLite-backed store for password files produced by
+//! `server_registration_finish`, keyed by credential identifier.
+
+use crate::{
+    common::{DEFAULT_CIPHER_SUITE_ID, PASSWORD_FILE_VERSION},
+    inspect,
+};
+use rusqlite::{params, Connection, OptionalExtension, TransactionBehavior};
+use std::sync::{Arc, Mutex};
+
+/// Schema migrations, applied in order. The index + 1 of a migration is the
+/// schema version it brings the database to (tracked in `PRAGMA user_version`).
+const MIGRATIONS: &[&str] = &["CREATE TABLE password_files (
+        credential_identifier BLOB PRIMARY KEY NOT NULL,
+        password_file BLOB NOT NULL,
+        suite TEXT NOT NULL,
+        version INTEGER NOT NULL,
+        created_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now')),
+        updated_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now'))
+    );"];
+
+#[derive(Debug, thiserror::Error, uniffi::Error)]
+pub enum CredentialStoreError {
+    #[error("Credential store failed: {0}")]
+    Generic(String),
+    #[error("A password file already exists for this credential identifier")]
+    AlreadyExists,
+    #[error("No password file exists for this credential identifier")]
+    NotFound,
+    #[error("Password file is malformed: {0}")]
+    InvalidPasswordFile(String),
+    #[error("Stored password file uses suite {suite} version {version}")]
+    UnsupportedRecord { suite: String, version: u32 },
+}
+
+impl From<rusqlite::Error> for CredentialStoreError {
+    fn from(e: rusqlite::Error) -> Self {
+        CredentialStoreError::Generic(format!("{:?}", e))
+    }
+}
+
+/// A persistent store of password files backed by a single SQLite database file.
+#[derive(uniffi::Object)]
+pub struct SqliteCredentialStore {
+    conn: Mutex<Connection>,
+}
+
+#[uniffi::export]
+impl SqliteCredentialStore {
+    /// Opens (or creates) the database at `path` and applies any pending schema migrations.
+    #[uniffi::constructor]
+    pub fn open(path: String) -> Result<Arc<Self>, CredentialStoreError> {
+        let mut conn = Connection::open(path)?;
+        migrate(&mut conn)?;
+
+        Ok(Arc::new(Self {
+            conn: Mutex::new(conn),
+        }))
+    }
+
+    /// Returns the schema version the database is currently at.
+    pub fn schema_version(&self) -> Result<u32, CredentialStoreError> {
+        let conn = self.lock()?;
+        Ok(user_version(&conn)?)
+    }
+
+    /// Stores the password file for a new credential identifier.
+    ///
+    /// Fails with `AlreadyExists` if the identifier is already registered.
+    pub fn insert(
+        &self,
+        credential_identifier: Vec<u8>,
+        password_file: Vec<u8>,
+    ) -> Result<(), CredentialStoreError> {
+        validate_password_file(&password_file)?;
+
+        let conn = self.lock()?;
+        let inserted = conn.execute(
+            "INSERT INTO password_files (credential_identifier, password_file, suite, version)
+             VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT (credential_identifier) DO NOTHING",
+            params![
+                credential_identifier,
+                password_file,
+                DEFAULT_CIPHER_SUITE_ID,
+                PASSWORD_FILE_VERSION
+            ],
+        )?;
+
+        match inserted {
+            0 => Err(CredentialStoreError::AlreadyExists),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the password file for a credential identifier, if one is stored.
+    ///
+    /// Fails with `UnsupportedRecord` if the row was written for another suite or version.
+    pub fn get(
+        &self,
+        credential_identifier: Vec<u8>,
+    ) -> Result<Option<Vec<u8>>, CredentialStoreError> {
+        let conn = self.lock()?;
+        let row = conn
+            .query_row(
+                "SELECT password_file, suite, version FROM password_files
+                 WHERE credential_identifier = ?1",
+                params![credential_identifier],
+                |row| {
+                    Ok((
+                        row.get::<_, Vec<u8>>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, u32>(2)?,
+                    ))
+                },
+            )
+            .optional()?;
+
+        match row {
+            None => Ok(None),
+            Some((password_file, suite, version))
+                if suite == DEFAULT_CIPHER_SUITE_ID && version == PASSWORD_FILE_VERSION =>
+            {
+                Ok(Some(password_file))
+            }
+            Some((_, suite, version)) => {
+                Err(CredentialStoreError::UnsupportedRecord { suite, version })
+            }
+        }
+    }
+
+    /// Atomically replaces the password file of an existing credential identifier,
+    /// e.g. after a password change.
+    ///
+    /// Fails with `NotFound` if the identifier is not registered, leaving the store untouched.
+    pub fn replace(
+        &self,
+        credential_identifier: Vec<u8>,
+        password_file: Vec<u8>,
+    ) -> Result<(), CredentialStoreError> {
+        validate_password_file(&password_file)?;
+
+        let mut conn = self.lock()?;
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        let updated = tx.execute(
+            "UPDATE password_files
+             SET password_file = ?2, suite = ?3, version = ?4, updated_at = strftime('%s', 'now')
+             WHERE credential_identifier = ?1",
+            params![
+                credential_identifier,
+                password_file,
+                DEFAULT_CIPHER_SUITE_ID,
+                PASSWORD_FILE_VERSION
+            ],
+        )?;
+
+        if updated == 0 {
+            return Err(CredentialStoreError::NotFound);
+        }
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// Removes the password file for a credential identifier.
+    ///
+    /// Returns whether a password file was removed.
+    pub fn delete(&self, credential_identifier: Vec<u8>) -> Result<bool, CredentialStoreError> {
+        let conn = self.lock()?;
+        let deleted = conn.execute(
+            "DELETE FROM password_files WHERE credential_identifier = ?1",
+            params![credential_identifier],
+        )?;
+
+        Ok(deleted > 0)
+    }
+
+    /// Removes the password file of a one-time credential, e.g. a recovery code, after a
+    /// successful login with it.
+    ///
+    /// Fails with `NotFound` if it was already removed, so of concurrent logins with the same
+    /// credential only the one that consumes it should be completed.
+    pub fn consume(&self, credential_identifier: Vec<u8>) -> Result<(), CredentialStoreError> {
+        match self.delete(credential_identifier)? {
+            true => Ok(()),
+            false => Err(CredentialStoreError::NotFound),
+        }
+    }
+}
+
+impl SqliteCredentialStore {
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, Connection>, CredentialStoreError> {
+        self.conn
+            .lock()
+            .map_err(|e| CredentialStoreError::Generic(format!("{:?}", e)))
+    }
+}
+
+fn user_version(conn: &Connection) -> rusqlite::Result<u32> {
+    conn.query_row("PRAGMA user_version", [], |row| row.get(0))
+}
+
+/// Brings the schema up to date, applying each pending migration in its own transaction.
+fn migrate(conn: &mut Connection) -> Result<(), CredentialStoreError> {
+    let current = user_version(conn)? as usize;
+    if current > MIGRATIONS.len() {
+        return Err(CredentialStoreError::Generic(format!(
+            "Database schema version {} is newer than supported version {}",
+            current,
+            MIGRATIONS.len()
+        )));
+    }
+
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(current) {
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Exclusive)?;
+        tx.execute_batch(migration)?;
+        tx.pragma_update(None, "user_version", (index + 1) as u32)?;
+        tx.commit()?;
+    }
+
+    Ok(())
+}
+
+fn validate_password_file(password_file: &[u8]) -> Result<(), CredentialStoreError> {
+    inspect::validate_password_file(password_file.to_vec())
+        .map_err(|e| CredentialStoreError::InvalidPasswordFile(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        client_registration::{client_registration_finish, client_registration_start},
+        common::IdentityMode,
+        server::server_setup,
+        server_registration::{server_registration_finish, server_registration_start},
+    };
+
+    fn register(server_setup_bytes: &[u8], password: &[u8], client_id: &[u8]) -> Vec<u8> {
+        let start = client_registration_start(password.to_vec()).unwrap();
+        let response = server_registration_start(
+            server_setup_bytes.to_vec(),
+            start.registration_request,
+            client_id,
+        )
+        .unwrap();
+        let finish = client_registration_finish(
+            password.to_vec(),
+            start.client_registration,
+            response,
+            IdentityMode::DefaultPublicKeys,
+        )
+        .unwrap();
+
+        server_registration_finish(finish.registration_upload).unwrap()
+    }
+
+    fn temp_store() -> (tempfile::TempDir, String) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir
+            .path()
+            .join("credentials.db")
+            .to_str()
+            .unwrap()
+            .to_string();
+        (dir, path)
+    }
+
+    #[test]
+    fn insert_get_replace_delete() {
+        let (_dir, path) = temp_store();
+        let store = SqliteCredentialStore::open(path).unwrap();
+        let setup = server_setup();
+        let client_id = b"alice".to_vec();
+
+        let password_file = register(&setup, b"password", &client_id);
+        store
+            .insert(client_id.clone(), password_file.clone())
+            .unwrap();
+        assert_eq!(
+            store.get(client_id.clone()).unwrap(),
+            Some(password_file.clone())
+        );
+
+        assert!(matches!(
+            store.insert(client_id.clone(), password_file.clone()),
+            Err(CredentialStoreError::AlreadyExists)
+        ));
+
+        let new_password_file = register(&setup, b"new password", &client_id);
+        store
+            .replace(client_id.clone(), new_password_file.clone())
+            .unwrap();
+        assert_eq!(
+            store.get(client_id.clone()).unwrap(),
+            Some(new_password_file)
+        );
+
+        assert!(store.delete(client_id.clone()).unwrap());
+        assert_eq!(store.get(client_id.clone()).unwrap(), None);
+        assert!(matches!(
+            store.replace(client_id, password_file),
+            Err(CredentialStoreError::NotFound)
+        ));
+    }
+
+    #[test]
+    fn one_time_credential_is_consumed_once() {
+        let (_dir, path) = temp_store();
+        let store = SqliteCredentialStore::open(path).unwrap();
+        let client_id = b"recovery".to_vec();
+        store
+            .insert(
+                client_id.clone(),
+                register(&server_setup(), b"code", &client_id),
+            )
+            .unwrap();
+
+        store.consume(client_id.clone()).unwrap();
+        assert_eq!(store.get(client_id.clone()).unwrap(), None);
+        assert!(matches!(
+            store.consume(client_id),
+            Err(CredentialStoreError::NotFound)
+        ));
+    }
+
+    #[test]
+    fn reopen_keeps_data_and_schema() {
+        let (_dir, path) = temp_store();
+        let client_id = b"bob".to_vec();
+        let password_file = register(&server_setup(), b"password", &client_id);
+
+        {
+            let store = SqliteCredentialStore::open(path.clone()).unwrap();
+            store
+                .insert(client_id.clone(), password_file.clone())
+                .unwrap();
+        }
+
+        let store = SqliteCredentialStore::open(path).unwrap();
+        assert_eq!(store.schema_version().unwrap(), MIGRATIONS.len() as u32);
+        assert_eq!(store.get(client_id).unwrap(), Some(password_file));
+    }
+
+    #[test]
+    fn malformed_password_file_is_rejected() {
+        let (_dir, path) = temp_store();
+        let store = SqliteCredentialStore::open(path).unwrap();
+
+        assert!(matches!(
+            store.insert(b"carol".to_vec(), vec![0u8; 12]),
+            Err(CredentialStoreError::InvalidPasswordFile(_))
+        ));
+    }
+
+    #[test]
+    fn other_suite_is_rejected_on_read() {
+        let (_dir, path) = temp_store();
+        let store = SqliteCredentialStore::open(path).unwrap();
+        let client_id = b"dave".to_vec();
+        store
+            .insert(
+                client_id.clone(),
+                register(&server_setup(), b"password", &client_id),
+            )
+            .unwrap();
+
+        store
+            .lock()
+            .unwrap()
+            .execute("UPDATE password_files SET suite = 'other'", [])
+            .unwrap();
+
+        assert!(matches!(
+            store.get(client_id),
+            Err(CredentialStoreError::UnsupportedRecord { .. })
+        ));
+    }
+}
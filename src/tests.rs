@@ -3,18 +3,51 @@
 // SPDX-License-Identifier: EUPL-1.2
 
 use crate::{
-    client_login::{client_login_finish, client_login_start},
-    client_registration::{client_registration_finish, client_registration_start},
-    server::server_setup,
-    server_login::{server_login_finish, server_login_start},
+    channel_binding::channel_binding_context,
+    client_driver::{
+        run_login, run_registration, session_key_fingerprint, DriverConfig, DriverError,
+        LocalServer,
+    },
+    client_login::{
+        client_login_finish, client_login_start, ClientLoginFinishResult, ClientLoginStartResult,
+    },
+    client_registration::{
+        client_registration_finish, client_registration_start, ClientRegistrationFinishResult,
+    },
+    common::IdentityMode,
+    credential_identifier::{
+        credential_identifier_derive, credential_identifier_generate_pepper, IdentifierKind,
+    },
+    device_enrolment::{
+        enrolment_create_account, enrolment_sign_device, enrolment_unwrap_account_secret,
+        enrolment_verify_device,
+    },
+    inspect::inspect_password_file,
+    login_context::{diff_login_contexts, encode_login_context, LoginContext, LoginContextField},
+    oprf::OprfSuite,
+    password::{
+        password_prepare_login, password_prepare_registration, PasswordNormalization,
+        PasswordPolicy, PasswordScore,
+    },
+    server::{server_public_key, server_setup},
+    server_login::{server_login_finish, server_login_start, ServerLoginStartResult},
     server_registration::{server_registration_finish, server_registration_start},
+    signing::{signing_public_key, signing_sign, signing_verify, SigningAlgorithm},
+    threshold_oprf::{
+        threshold_oprf_client_blind, threshold_oprf_client_finalize, threshold_oprf_generate_key,
+        threshold_oprf_server_evaluate, ThresholdOprfKey,
+    },
 };
+use std::sync::Arc;
 
 #[test]
 fn full_flow_is_ok() {
     let password = b"password".to_vec();
     let client_id = b"client".to_vec();
-    let server_id = b"server".to_vec();
+    let identity_mode = IdentityMode::Explicit {
+        client: client_id.clone(),
+        server: b"server".to_vec(),
+    };
     let context = b"context".to_vec();
 
     let server_setup_bytes = server_setup();
@@ -34,8 +67,7 @@ fn full_flow_is_ok() {
         password.clone(),
         client_registration_state,
         registration_response,
-        Some(client_id.clone()),
-        Some(server_id.clone()),
+        identity_mode.clone(),
     )
     .unwrap();
     let registration_upload = client_reg_finish_result.registration_upload;
@@ -54,13 +86,12 @@ fn full_flow_is_ok() {
 
     // Server login start
     let server_login_start_result = server_login_start(
-        server_setup_bytes,
-        password_file,
+        server_setup_bytes.clone(),
+        password_file.clone(),
         credential_request,
         &client_id,
         Some(context.clone()),
-        Some(client_id.clone()),
-        Some(server_id.clone()),
+        identity_mode.clone(),
     )
     .unwrap();
     let credential_response = server_login_start_result.credential_response;
@@ -72,8 +103,7 @@ fn full_flow_is_ok() {
         client_login_state,
         password,
         context.clone(),
-        client_id.clone(),
-        server_id.clone(),
+        identity_mode.clone(),
     )
     .unwrap();
     let credential_finalization = client_login_finish_result.credential_finalization;
@@ -84,8 +114,7 @@ fn full_flow_is_ok() {
         server_login_state,
         credential_finalization,
         Some(context),
-        Some(client_id),
-        Some(server_id),
+        identity_mode,
     )
     .unwrap();
 
@@ -104,7 +133,151 @@ fn full_flow_is_ok() {
     assert_eq!(
         client_reg_finish_result.export_key, client_login_finish_result.export_key,
         "Export keys should match"
+    );
+    assert_eq!(
+        client_reg_finish_result.client_public_key, client_login_finish_result.client_public_key,
+        "Client public keys should match"
+    );
+    assert_eq!(
+        client_login_finish_result.client_public_key,
+        inspect_password_file(password_file)
+            .unwrap()
+            .client_public_key,
+        "Client public key should be the one in the password file"
+    );
+    assert_eq!(
+        client_login_finish_result.server_public_key,
+        server_public_key(server_setup_bytes).unwrap(),
+        "Server public key should be the one of the server setup"
+    );
+}
+
+/// Registers `password` under `credential_identifier`, returning the client's finish result
+/// and the password file.
+fn register(
+    server_setup_bytes: &[u8],
+    credential_identifier: &[u8],
+    password: &[u8],
+    identity_mode: IdentityMode,
+) -> (ClientRegistrationFinishResult, Vec<u8>) {
+    let client_reg_start_result = client_registration_start(password.to_vec()).unwrap();
+    let registration_response = server_registration_start(
+        server_setup_bytes.to_vec(),
+        client_reg_start_result.registration_request,
+        credential_identifier,
+    )
+    .unwrap();
+    let client_reg_finish_result = client_registration_finish(
+        password.to_vec(),
+        client_reg_start_result.client_registration,
+        registration_response,
+        identity_mode,
+    )
+    .unwrap();
+    let password_file =
+        server_registration_finish(client_reg_finish_result.registration_upload.clone()).unwrap();
+    (client_reg_finish_result, password_file)
+}
+
+/// Starts a login with `password` against `password_file`, returning the client's and the
+/// server's start results.
+fn login_start(
+    server_setup_bytes: &[u8],
+    credential_identifier: &[u8],
+    password: &[u8],
+    password_file: Vec<u8>,
+    context: &[u8],
+    identity_mode: IdentityMode,
+) -> (ClientLoginStartResult, ServerLoginStartResult) {
+    let client_login_start_result = client_login_start(password.to_vec()).unwrap();
+    let server_login_start_result = server_login_start(
+        server_setup_bytes.to_vec(),
+        password_file,
+        client_login_start_result.credential_request.clone(),
+        credential_identifier,
+        Some(context.to_vec()),
+        identity_mode,
+    )
+    .unwrap();
+    (client_login_start_result, server_login_start_result)
+}
+
+/// Logs in with `password` against `password_file`, returning the client's finish result if
+/// both sides finished with the same session key.
+fn login(
+    server_setup_bytes: &[u8],
+    credential_identifier: &[u8],
+    password: &[u8],
+    password_file: Vec<u8>,
+    identity_mode: IdentityMode,
+) -> Option<ClientLoginFinishResult> {
+    let context = b"context".to_vec();
+    let (client_login_start_result, server_login_start_result) = login_start(
+        server_setup_bytes,
+        credential_identifier,
+        password,
+        password_file,
+        &context,
+        identity_mode.clone(),
+    );
+    let client_login_finish_result = client_login_finish(
+        server_login_start_result.credential_response,
+        client_login_start_result.client_registration,
+        password.to_vec(),
+        context.clone(),
+        identity_mode.clone(),
+    )
+    .ok()?;
+    let server_session_key = server_login_finish(
+        server_login_start_result.server_login,
+        client_login_finish_result.credential_finalization.clone(),
+        Some(context),
+        identity_mode,
+    )
+    .ok()?;
+    (server_session_key == client_login_finish_result.session_key)
+        .then_some(client_login_finish_result)
+}
+
+#[test]
+fn signing_key_is_recreated_at_login() {
+    let server_setup_bytes = server_setup();
+    let (client_reg_finish_result, password_file) = register(
+        &server_setup_bytes,
+        b"client",
+        b"password",
+        IdentityMode::DefaultPublicKeys,
+    );
+    let client_login_finish_result = login(
+        &server_setup_bytes,
+        b"client",
+        b"password",
+        password_file,
+        IdentityMode::DefaultPublicKeys,
     )
+    .unwrap();
+
+    // A signing key derived at registration is recreated at login
+    let registration_signing_key = signing_public_key(
+        SigningAlgorithm::Ed25519,
+        client_reg_finish_result.export_key,
+        b"recovery".to_vec(),
+    )
+    .unwrap();
+    let signature = signing_sign(
+        SigningAlgorithm::Ed25519,
+        client_login_finish_result.export_key,
+        b"recovery".to_vec(),
+        b"recovery request".to_vec(),
+    )
+    .unwrap();
+    assert!(signing_verify(
+        SigningAlgorithm::Ed25519,
+        registration_signing_key,
+        b"recovery request".to_vec(),
+        signature,
+    )
+    .unwrap());
 }
 
 #[test]
@@ -112,7 +285,10 @@ fn wrong_password_is_err() {
     let correct_password = b"password".to_vec();
     let wrong_password = b"wrong_password".to_vec();
     let client_id = b"client".to_vec();
-    let server_id = b"server".to_vec();
+    let identity_mode = IdentityMode::Explicit {
+        client: client_id.clone(),
+        server: b"server".to_vec(),
+    };
     let context = b"context".to_vec();
 
     let server_setup_bytes = server_setup();
@@ -132,8 +308,7 @@ fn wrong_password_is_err() {
         correct_password.clone(),
         client_registration_state,
         registration_response,
-        Some(client_id.clone()),
-        Some(server_id.clone()),
+        identity_mode.clone(),
     )
     .unwrap();
     let registration_upload = client_reg_finish_result.registration_upload;
@@ -153,8 +328,7 @@ fn wrong_password_is_err() {
         credential_request,
         &client_id,
         Some(context.clone()),
-        Some(client_id.clone()),
-        Some(server_id.clone()),
+        identity_mode.clone(),
     )
     .unwrap();
     let credential_response = server_login_start_result.credential_response;
@@ -165,8 +339,7 @@ fn wrong_password_is_err() {
         client_login_state,
         wrong_password,
         context,
-        client_id,
-        server_id,
+        identity_mode,
     );
 
     assert!(client_login_finish_result.is_err());
@@ -176,7 +349,10 @@ fn wrong_password_is_err() {
 fn login_with_no_password_file_is_err() {
     let password = b"password".to_vec();
     let client_id = b"client".to_vec();
-    let server_id = b"server".to_vec();
+    let identity_mode = IdentityMode::Explicit {
+        client: client_id.clone(),
+        server: b"server".to_vec(),
+    };
     let context = b"context".to_vec();
 
     let server_setup_bytes = server_setup();
@@ -193,8 +369,7 @@ fn login_with_no_password_file_is_err() {
         credential_request,
         &client_id,
         Some(context.clone()),
-        Some(client_id.clone()),
-        Some(server_id.clone()),
+        identity_mode.clone(),
     )
     .unwrap();
     let credential_response = server_login_start_result.credential_response;
@@ -205,9 +380,527 @@ fn login_with_no_password_file_is_err() {
         client_login_state,
         password,
         context,
-        client_id,
-        server_id,
+        identity_mode,
     );
 
     assert!(client_login_finish_result.is_err());
 }
+
+#[test]
+fn client_driver_against_local_server_is_ok() {
+    let config = DriverConfig {
+        context: b"context".to_vec(),
+        identity_mode: IdentityMode::Explicit {
+            client: b"client".to_vec(),
+            server: b"server".to_vec(),
+        },
+    };
+    let server = Arc::new(LocalServer::new(server_setup(), config.clone()));
+    let credential_identifier = b"alice".to_vec();
+
+    let registration = run_registration(
+        server.clone(),
+        credential_identifier.clone(),
+        b"password".to_vec(),
+        config.clone(),
+    )
+    .unwrap();
+    assert_eq!(registration.steps.len(), 2);
+
+    let login = run_login(
+        server.clone(),
+        credential_identifier.clone(),
+        b"password".to_vec(),
+        config.clone(),
+    )
+    .unwrap();
+    assert_eq!(login.steps.len(), 2);
+    assert_eq!(login.export_key, registration.export_key);
+    assert_eq!(
+        login.session_key_fingerprint,
+        session_key_fingerprint(&server.session_key(&credential_identifier).unwrap())
+    );
+
+    let wrong_password = run_login(
+        server,
+        credential_identifier,
+        b"wrong_password".to_vec(),
+        config,
+    );
+    assert!(matches!(wrong_password, Err(DriverError::Login(_))));
+}
+
+#[test]
+fn identity_modes_must_agree() {
+    let explicit = |client: &[u8], server: &[u8]| IdentityMode::Explicit {
+        client: client.to_vec(),
+        server: server.to_vec(),
+    };
+    let modes = [
+        IdentityMode::DefaultPublicKeys,
+        explicit(b"alice", b"server"),
+        explicit(b"alice", b"other-server"),
+        explicit(b"bob", b"server"),
+        explicit(b"", b""),
+    ];
+    let server_setup_bytes = server_setup();
+    for registration_mode in &modes {
+        let (_, password_file) = register(
+            &server_setup_bytes,
+            b"client",
+            b"password",
+            registration_mode.clone(),
+        );
+        for login_mode in &modes {
+            assert_eq!(
+                login(
+                    &server_setup_bytes,
+                    b"client",
+                    b"password",
+                    password_file.clone(),
+                    login_mode.clone(),
+                )
+                .is_some(),
+                registration_mode == login_mode,
+                "registered with {:?}, logged in with {:?}",
+                registration_mode,
+                login_mode
+            );
+        }
+    }
+}
+
+#[test]
+fn mismatched_channel_binding_is_err() {
+    let password = b"password".to_vec();
+    let client_id = b"client".to_vec();
+    let identity_mode = IdentityMode::Explicit {
+        client: client_id.clone(),
+        server: b"server".to_vec(),
+    };
+    let server_setup_bytes = server_setup();
+    let binding = |exporter_value: &[u8]| {
+        channel_binding_context(
+            b"EXPORTER-Channel-Binding".to_vec(),
+            exporter_value.to_vec(),
+            vec![b"login".to_vec()],
+        )
+        .unwrap()
+    };
+    let server_binding = binding(&[1; 32]);
+
+    let (_, password_file) = register(
+        &server_setup_bytes,
+        &client_id,
+        &password,
+        identity_mode.clone(),
+    );
+    let start = || {
+        login_start(
+            &server_setup_bytes,
+            &client_id,
+            &password,
+            password_file.clone(),
+            &server_binding,
+            identity_mode.clone(),
+        )
+    };
+
+    // The same TLS session on both sides
+    let (client_start, server_start) = start();
+    let client_finish = client_login_finish(
+        server_start.credential_response,
+        client_start.client_registration,
+        password.clone(),
+        server_binding.clone(),
+        identity_mode.clone(),
+    )
+    .unwrap();
+    let server_session_key = server_login_finish(
+        server_start.server_login.clone(),
+        client_finish.credential_finalization.clone(),
+        Some(server_binding.clone()),
+        identity_mode.clone(),
+    )
+    .unwrap();
+    assert_eq!(client_finish.session_key, server_session_key);
+
+    // A state without the version tag, as from releases before it, is rejected
+    assert!(server_login_finish(
+        server_start.server_login[1..].to_vec(),
+        client_finish.credential_finalization.clone(),
+        Some(server_binding.clone()),
+        identity_mode.clone(),
+    )
+    .is_err());
+
+    // The server finishes with another identity mode
+    assert!(server_login_finish(
+        server_start.server_login.clone(),
+        client_finish.credential_finalization.clone(),
+        Some(server_binding.clone()),
+        IdentityMode::DefaultPublicKeys,
+    )
+    .is_err());
+
+    // The server finishes with the binding of another TLS session
+    assert!(server_login_finish(
+        server_start.server_login,
+        client_finish.credential_finalization,
+        Some(binding(&[2; 32])),
+        identity_mode.clone(),
+    )
+    .is_err());
+
+    // The client is on another TLS session, e.g. behind a TLS-terminating relay
+    let (client_start, server_start) = start();
+    assert!(client_login_finish(
+        server_start.credential_response,
+        client_start.client_registration,
+        password,
+        binding(&[2; 32]),
+        identity_mode,
+    )
+    .is_err());
+}
+
+#[test]
+fn mismatched_login_context_is_diagnosed() {
+    let password = b"password".to_vec();
+    let client_id = b"client".to_vec();
+    let identity_mode = IdentityMode::Explicit {
+        client: client_id.clone(),
+        server: b"server".to_vec(),
+    };
+    let server_setup_bytes = server_setup();
+    let server_context = LoginContext {
+        app_id: "se.digg.app".into(),
+        protocol_version: 1,
+        device_id: "device".into(),
+        purpose: "login".into(),
+        channel_binding: None,
+    };
+    let client_context = LoginContext {
+        protocol_version: 2,
+        ..server_context.clone()
+    };
+    let server_context = encode_login_context(server_context).unwrap();
+    let client_context = encode_login_context(client_context).unwrap();
+
+    let (_, password_file) = register(
+        &server_setup_bytes,
+        &client_id,
+        &password,
+        identity_mode.clone(),
+    );
+    let (client_login_start_result, server_login_start_result) = login_start(
+        &server_setup_bytes,
+        &client_id,
+        &password,
+        password_file,
+        &server_context,
+        identity_mode.clone(),
+    );
+    assert!(client_login_finish(
+        server_login_start_result.credential_response,
+        client_login_start_result.client_registration,
+        password,
+        client_context.clone(),
+        identity_mode,
+    )
+    .is_err());
+
+    assert_eq!(
+        diff_login_contexts(client_context, server_context).unwrap(),
+        vec![LoginContextField::ProtocolVersion]
+    );
+}
+
+/// Hardens a password with the threshold OPRF, evaluated by the servers holding the
+/// `indices` shares.
+fn harden_password(key: &ThresholdOprfKey, indices: &[u16], password: &[u8]) -> Vec<u8> {
+    let suite = OprfSuite::Ristretto255Sha512;
+    let mut input = (b"client".len() as u16).to_be_bytes().to_vec();
+    input.extend_from_slice(b"client");
+    input.extend_from_slice(password);
+
+    let blind = threshold_oprf_client_blind(suite, input.clone()).unwrap();
+    let partials = indices
+        .iter()
+        .map(|&index| {
+            threshold_oprf_server_evaluate(
+                suite,
+                key.shares[index as usize - 1].clone(),
+                blind.blinded_element.clone(),
+            )
+            .unwrap()
+        })
+        .collect();
+    let public_keys = key.shares.iter().map(|s| s.public_key.clone()).collect();
+    threshold_oprf_client_finalize(
+        suite,
+        blind.client_state,
+        input,
+        key.threshold,
+        partials,
+        public_keys,
+    )
+    .unwrap()
+}
+
+#[test]
+fn threshold_hardened_password_flow_is_ok() {
+    let identity_mode = IdentityMode::Explicit {
+        client: b"client".to_vec(),
+        server: b"server".to_vec(),
+    };
+    let server_setup_bytes = server_setup();
+    let key = threshold_oprf_generate_key(OprfSuite::Ristretto255Sha512, 2, 3).unwrap();
+
+    // Register through servers 1 and 2
+    let (_, password_file) = register(
+        &server_setup_bytes,
+        b"client",
+        &harden_password(&key, &[1, 2], b"password"),
+        identity_mode.clone(),
+    );
+
+    // Log in through servers 2 and 3
+    let login_with = |password: &[u8]| {
+        login(
+            &server_setup_bytes,
+            b"client",
+            &harden_password(&key, &[2, 3], password),
+            password_file.clone(),
+            identity_mode.clone(),
+        )
+    };
+    assert!(login_with(b"password").is_some());
+    assert!(login_with(b"wrong_password").is_none());
+}
+
+#[test]
+fn second_device_enrolment_is_ok() {
+    let identity_mode = IdentityMode::Explicit {
+        client: b"client".to_vec(),
+        server: b"server".to_vec(),
+    };
+    let server_setup_bytes = server_setup();
+
+    // The phone registers and creates the account secret
+    let (client_reg_finish_result, password_file) = register(
+        &server_setup_bytes,
+        b"client",
+        b"password",
+        identity_mode.clone(),
+    );
+    let account = enrolment_create_account(client_reg_finish_result.export_key).unwrap();
+
+    // The server stores the wrapped secret and the account public key, and enrols the phone
+    let wrapped_account_secret = account.wrapped_account_secret;
+    let account_public_key = account.account_public_key;
+    let phone_key = b"phone device key".to_vec();
+    let phone_signature = enrolment_sign_device(
+        account.account_secret.clone(),
+        "phone".into(),
+        phone_key.clone(),
+    )
+    .unwrap();
+    assert!(enrolment_verify_device(
+        account_public_key.clone(),
+        "phone".into(),
+        phone_key,
+        phone_signature,
+    )
+    .unwrap());
+
+    // The tablet logs in and gets the wrapped secret back
+    let client_login_finish_result = login(
+        &server_setup_bytes,
+        b"client",
+        b"password",
+        password_file,
+        identity_mode,
+    )
+    .unwrap();
+    let tablet_account_secret = enrolment_unwrap_account_secret(
+        client_login_finish_result.export_key,
+        wrapped_account_secret,
+    )
+    .unwrap();
+    assert_eq!(tablet_account_secret, account.account_secret);
+
+    // The tablet enrols its own device key with the account key
+    let tablet_key = b"tablet device key".to_vec();
+    let tablet_signature =
+        enrolment_sign_device(tablet_account_secret, "tablet".into(), tablet_key.clone()).unwrap();
+    assert!(enrolment_verify_device(
+        account_public_key.clone(),
+        "tablet".into(),
+        tablet_key,
+        tablet_signature.clone(),
+    )
+    .unwrap());
+
+    // The enrolment cannot be replayed for another device key
+    assert!(!enrolment_verify_device(
+        account_public_key,
+        "tablet".into(),
+        b"attacker device key".to_vec(),
+        tablet_signature,
+    )
+    .unwrap());
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn recovery_code_login_is_ok_once() {
+    use crate::{
+        device_enrolment::enrolment_wrap_account_secret,
+        recovery::{recovery_generate_codes, recovery_parse_code},
+        sqlite_store::{CredentialStoreError, SqliteCredentialStore},
+    };
+
+    let account_id = b"client".to_vec();
+    let server_setup_bytes = server_setup();
+    let dir = tempfile::tempdir().unwrap();
+    let store =
+        SqliteCredentialStore::open(dir.path().join("credentials.db").display().to_string())
+            .unwrap();
+    let account_secret = enrolment_create_account(vec![1; 64])
+        .unwrap()
+        .account_secret;
+
+    // The client registers every code and wraps the account secret under its export key,
+    // and the server stores the password files and the wrapped secrets
+    let codes = recovery_generate_codes(account_id.clone(), 2).unwrap();
+    let mut wrapped_account_secrets = std::collections::HashMap::new();
+    for code in &codes {
+        let (client_reg_finish_result, password_file) = register(
+            &server_setup_bytes,
+            &code.credential_identifier,
+            &code.password,
+            IdentityMode::DefaultPublicKeys,
+        );
+        store
+            .insert(code.credential_identifier.clone(), password_file)
+            .unwrap();
+        wrapped_account_secrets.insert(
+            code.credential_identifier.clone(),
+            enrolment_wrap_account_secret(
+                client_reg_finish_result.export_key,
+                account_secret.clone(),
+            )
+            .unwrap(),
+        );
+    }
+
+    // Logs in with a code against the store
+    let recover = |credential_identifier: &[u8], password: &[u8]| {
+        let password_file = store.get(credential_identifier.to_vec()).unwrap()?;
+        login(
+            &server_setup_bytes,
+            credential_identifier,
+            password,
+            password_file,
+            IdentityMode::DefaultPublicKeys,
+        )
+    };
+
+    // The user types in the first code, and two logins with it complete concurrently
+    let typed = codes[0].code.to_lowercase().replace('-', " ");
+    let code = recovery_parse_code(account_id.clone(), typed).unwrap();
+    assert!(code == codes[0]);
+    let logins = [
+        recover(&code.credential_identifier, &code.password).unwrap(),
+        recover(&code.credential_identifier, &code.password).unwrap(),
+    ];
+
+    // The server consumes the code after each, and only releases the recovery to the one
+    // that consumed it
+    let mut recovered = Vec::new();
+    for client_login_finish_result in logins {
+        match store.consume(code.credential_identifier.clone()) {
+            Ok(()) => recovered.push(
+                enrolment_unwrap_account_secret(
+                    client_login_finish_result.export_key,
+                    wrapped_account_secrets[&code.credential_identifier].clone(),
+                )
+                .unwrap(),
+            ),
+            Err(CredentialStoreError::NotFound) => {}
+            Err(e) => panic!("{:?}", e),
+        }
+    }
+    assert_eq!(recovered, [account_secret]);
+
+    // The code cannot be used again, while the other one still works
+    assert!(recover(&code.credential_identifier, &code.password).is_none());
+    assert!(recover(&codes[1].credential_identifier, &codes[1].password).is_some());
+}
+
+#[test]
+fn normalized_passwords_log_in_across_platforms() {
+    // Registered in NFC on one platform, typed in NFD on another
+    let nfc = "Ett l\u{e5}ngt l\u{f6}senord, caf\u{e9}".to_string();
+    let nfd = "Ett la\u{30a}ngt lo\u{308}senord, cafe\u{301}".to_string();
+    let policy = PasswordPolicy {
+        normalization: PasswordNormalization::OpaqueString,
+        minimum_score: Some(PasswordScore::Fair),
+    };
+
+    let server_setup_bytes = server_setup();
+    let login_with_passwords = |registration_password: &[u8], login_password: &[u8]| {
+        let (_, password_file) = register(
+            &server_setup_bytes,
+            b"client",
+            registration_password,
+            IdentityMode::DefaultPublicKeys,
+        );
+        login(
+            &server_setup_bytes,
+            b"client",
+            login_password,
+            password_file,
+            IdentityMode::DefaultPublicKeys,
+        )
+        .is_some()
+    };
+
+    assert!(login_with_passwords(
+        &password_prepare_registration(nfc.clone(), policy).unwrap(),
+        &password_prepare_login(nfd.clone(), PasswordNormalization::OpaqueString).unwrap(),
+    ));
+    assert!(!login_with_passwords(nfc.as_bytes(), nfd.as_bytes()));
+}
+
+#[test]
+fn derived_credential_identifier_flow_is_ok() {
+    let server_setup_bytes = server_setup();
+    let pepper = credential_identifier_generate_pepper();
+
+    // Registered with the email as typed at sign-up, logged in with it as typed at login
+    let registration_id = credential_identifier_derive(
+        pepper.clone(),
+        "Alice@Example.com ".into(),
+        IdentifierKind::Email,
+    )
+    .unwrap();
+    let login_id =
+        credential_identifier_derive(pepper, "alice@example.com".into(), IdentifierKind::Email)
+            .unwrap();
+    let (_, password_file) = register(
+        &server_setup_bytes,
+        &registration_id,
+        b"password",
+        IdentityMode::DefaultPublicKeys,
+    );
+    assert!(login(
+        &server_setup_bytes,
+        &login_id,
+        b"password",
+        password_file,
+        IdentityMode::DefaultPublicKeys,
+    )
+    .is_some());
+}
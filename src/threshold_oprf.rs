@@ -0,0 +1,430 @@
+// SPDX-FileCopyrightText: 2026 Digg - Agency for Digital Government
+//
+// SPDX-License-Identifier: EUPL-1.2
+
+//! This module exports a threshold OPRF: the OPRF key is Shamir-shared across `n` servers,
+//! and the client combines the partial evaluations of any `t` of them into the output of
+//! the full key, so no single server can evaluate the OPRF on its own.
+//!
+//! Each server evaluates the client's blinded element in the RFC 9497 VOPRF mode with its
+//! share as the key, proving the evaluation against the share's public key, and the client
+//! verifies every partial evaluation before interpolating them. The combined output equals
+//! the VOPRF output of the shared key.
+//!
+//! opaque-ke evaluates the OPAQUE OPRF with a local key and binds that evaluation into the
+//! key exchange, so the threshold OPRF cannot replace it. Instead its output is used as the
+//! password passed to `client_registration_start`/`client_login_start` and their finish
+//! functions: a backend holding the server setup, the password files and fewer than `t`
+//! shares then cannot test password guesses offline. The input should bind the user, e.g.
+//! the length-prefixed user name followed by the password, so outputs are not shared
+//! between accounts.
+
+use crate::oprf::{
+    key_pair, oprf_client_blind, oprf_server_blind_evaluate, with_suite, OprfBlindResult,
+    OprfError, OprfMode, OprfSuite,
+};
+use p256::elliptic_curve::generic_array::typenum::Unsigned;
+use rand::rngs::OsRng;
+use sha2::Digest;
+use voprf::{CipherSuite, EvaluationElement, Group, Proof, VoprfClient};
+
+/// One server's share of a threshold OPRF key.
+#[derive(Clone, uniffi::Record)]
+pub struct OprfKeyShare {
+    /// The share's evaluation point, between 1 and the number of servers.
+    pub index: u16,
+    pub private_key: Vec<u8>,
+    /// The public key partial evaluations with this share are verified against.
+    pub public_key: Vec<u8>,
+}
+
+#[derive(Clone, uniffi::Record)]
+pub struct ThresholdOprfKey {
+    /// The public key of the shared key, whose VOPRF output the client obtains.
+    pub public_key: Vec<u8>,
+    /// The number of partial evaluations needed to compute the output.
+    pub threshold: u16,
+    /// One share per server, to be distributed and not kept together.
+    pub shares: Vec<OprfKeyShare>,
+}
+
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct ThresholdOprfPartialEvaluation {
+    /// The index of the share that evaluated.
+    pub index: u16,
+    pub evaluated_element: Vec<u8>,
+    /// The DLEQ proof against the share's public key.
+    pub proof: Vec<u8>,
+}
+
+/// The scalar of a share index, in the suite's scalar byte order.
+fn index_scalar<G: Group>(suite: OprfSuite, index: u16) -> Result<G::Scalar, OprfError> {
+    let mut bytes = vec![0u8; G::ScalarLen::USIZE];
+    match suite {
+        OprfSuite::P256Sha256 => {
+            bytes[G::ScalarLen::USIZE - 2..].copy_from_slice(&index.to_be_bytes())
+        }
+        OprfSuite::Ristretto255Sha512 => bytes[..2].copy_from_slice(&index.to_le_bytes()),
+    }
+    Ok(G::deserialize_scalar(&bytes)?)
+}
+
+/// Evaluates a random polynomial of degree `threshold - 1` with constant term `secret` at
+/// the indices 1 to `servers`.
+fn split_secret<G: Group>(
+    suite: OprfSuite,
+    secret: G::Scalar,
+    threshold: u16,
+    servers: u16,
+) -> Result<Vec<G::Scalar>, OprfError> {
+    let coefficients: Vec<G::Scalar> = (1..threshold)
+        .map(|_| G::random_scalar(&mut OsRng))
+        .collect();
+    (1..=servers)
+        .map(|index| {
+            let x = index_scalar::<G>(suite, index)?;
+            let mut share = secret;
+            let mut x_power = x;
+            for coefficient in &coefficients {
+                share = share + &(*coefficient * &x_power);
+                x_power = x_power * &x;
+            }
+            Ok(share)
+        })
+        .collect()
+}
+
+/// Interpolates the partial evaluations at zero: the sum of `lambda_i * Z_i` with the
+/// Lagrange coefficients `lambda_i` of the distinct `indices`.
+fn interpolate<G: Group>(
+    suite: OprfSuite,
+    indices: &[u16],
+    elements: &[G::Elem],
+) -> Result<G::Elem, OprfError> {
+    let xs = indices
+        .iter()
+        .map(|&index| index_scalar::<G>(suite, index))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut combined: Option<G::Elem> = None;
+    for (i, element) in elements.iter().enumerate() {
+        // lambda_i = prod_{j != i} x_j / (x_j - x_i), which is 1 for a single share.
+        let mut lambda: Option<G::Scalar> = None;
+        for (_, x_j) in xs.iter().enumerate().filter(|&(j, _)| j != i) {
+            let term = *x_j * &G::invert_scalar(*x_j - &xs[i]);
+            lambda = Some(lambda.map_or(term, |lambda| lambda * &term));
+        }
+        let term = lambda.map_or(*element, |lambda| *element * &lambda);
+        combined = Some(combined.map_or(term, |combined| combined + &term));
+    }
+    combined.ok_or_else(|| OprfError::InvalidParameter("no partial evaluations".into()))
+}
+
+/// The RFC 9497 `Finalize` hash of an input and its unblinded element.
+fn finalize_hash<H: Digest>(input: &[u8], unblinded_element: &[u8]) -> Result<Vec<u8>, OprfError> {
+    let input_len = u16::try_from(input.len())
+        .map_err(|_| OprfError::InvalidParameter("input is too long".into()))?;
+    Ok(H::new()
+        .chain_update(input_len.to_be_bytes())
+        .chain_update(input)
+        .chain_update((unblinded_element.len() as u16).to_be_bytes())
+        .chain_update(unblinded_element)
+        .chain_update(b"Finalize")
+        .finalize()
+        .to_vec())
+}
+
+fn check_threshold(threshold: u16, servers: u16) -> Result<(), OprfError> {
+    if threshold == 0 || threshold > servers {
+        return Err(OprfError::InvalidParameter(format!(
+            "the threshold must be between 1 and the number of servers ({})",
+            servers
+        )));
+    }
+    Ok(())
+}
+
+/// Splits `private_key` into shares for `servers` servers, any `threshold` of which can
+/// evaluate the OPRF together.
+///
+/// The dealer running this should erase `private_key` and every share once distributed.
+#[uniffi::export]
+pub fn threshold_oprf_split_key(
+    suite: OprfSuite,
+    private_key: Vec<u8>,
+    threshold: u16,
+    servers: u16,
+) -> Result<ThresholdOprfKey, OprfError> {
+    check_threshold(threshold, servers)?;
+    let public_key = key_pair(suite, private_key.clone())?.public_key;
+    with_suite!(suite, CS => {
+        type G = <CS as CipherSuite>::Group;
+        let secret = G::deserialize_scalar(&private_key)?;
+        let shares = split_secret::<G>(suite, secret, threshold, servers)?
+            .into_iter()
+            .zip(1..=servers)
+            .map(|(share, index)| OprfKeyShare {
+                index,
+                private_key: G::serialize_scalar(share).to_vec(),
+                public_key: G::serialize_elem(G::base_elem() * share).to_vec(),
+            })
+            .collect();
+        Ok(ThresholdOprfKey {
+            public_key,
+            threshold,
+            shares,
+        })
+    })
+}
+
+/// Generates a random OPRF key and splits it with [`threshold_oprf_split_key`], without
+/// returning the key itself.
+#[uniffi::export]
+pub fn threshold_oprf_generate_key(
+    suite: OprfSuite,
+    threshold: u16,
+    servers: u16,
+) -> Result<ThresholdOprfKey, OprfError> {
+    let private_key = with_suite!(suite, CS => {
+        type G = <CS as CipherSuite>::Group;
+        G::serialize_scalar(G::random_scalar(&mut OsRng)).to_vec()
+    });
+    threshold_oprf_split_key(suite, private_key, threshold, servers)
+}
+
+/// Blinds `input` on the client, for evaluation by the servers holding the shares.
+#[uniffi::export]
+pub fn threshold_oprf_client_blind(
+    suite: OprfSuite,
+    input: Vec<u8>,
+) -> Result<OprfBlindResult, OprfError> {
+    oprf_client_blind(suite, OprfMode::Voprf, input)
+}
+
+/// Evaluates a blinded element from a client with one server's share.
+#[uniffi::export]
+pub fn threshold_oprf_server_evaluate(
+    suite: OprfSuite,
+    share: OprfKeyShare,
+    blinded_element: Vec<u8>,
+) -> Result<ThresholdOprfPartialEvaluation, OprfError> {
+    let result = oprf_server_blind_evaluate(
+        suite,
+        OprfMode::Voprf,
+        share.private_key,
+        blinded_element,
+        None,
+    )?;
+    Ok(ThresholdOprfPartialEvaluation {
+        index: share.index,
+        evaluated_element: result.evaluated_element,
+        proof: result.proof.unwrap_or_default(),
+    })
+}
+
+/// Verifies the partial evaluations of at least `threshold` distinct servers and combines
+/// them into the OPRF output of the shared key.
+///
+/// `share_public_keys` holds the public key of every share in index order, i.e. that of
+/// share `i` at position `i - 1`, and must come from the client's configuration rather
+/// than from the servers.
+#[uniffi::export]
+pub fn threshold_oprf_client_finalize(
+    suite: OprfSuite,
+    client_state: Vec<u8>,
+    input: Vec<u8>,
+    threshold: u16,
+    partial_evaluations: Vec<ThresholdOprfPartialEvaluation>,
+    share_public_keys: Vec<Vec<u8>>,
+) -> Result<Vec<u8>, OprfError> {
+    let servers = u16::try_from(share_public_keys.len())
+        .map_err(|_| OprfError::InvalidParameter("too many share public keys".into()))?;
+    check_threshold(threshold, servers)?;
+    let mut indices: Vec<u16> = partial_evaluations.iter().map(|e| e.index).collect();
+    indices.sort_unstable();
+    indices.dedup();
+    if indices.len() != partial_evaluations.len() {
+        return Err(OprfError::InvalidParameter(
+            "partial evaluations must come from distinct shares".into(),
+        ));
+    }
+    if indices.len() < threshold as usize {
+        return Err(OprfError::InvalidParameter(format!(
+            "{} partial evaluations are needed, got {}",
+            threshold,
+            indices.len()
+        )));
+    }
+    if indices.iter().any(|&index| index == 0 || index > servers) {
+        return Err(OprfError::InvalidParameter(format!(
+            "share indices must be between 1 and {}",
+            servers
+        )));
+    }
+
+    with_suite!(suite, CS => {
+        type G = <CS as CipherSuite>::Group;
+        let client = VoprfClient::<CS>::deserialize(&client_state)?;
+        let mut elements = Vec::with_capacity(partial_evaluations.len());
+        for partial in &partial_evaluations {
+            let evaluation_element = EvaluationElement::<CS>::deserialize(&partial.evaluated_element)?;
+            let public_key = G::deserialize_elem(&share_public_keys[partial.index as usize - 1])?;
+            client.finalize(
+                &input,
+                &evaluation_element,
+                &Proof::<CS>::deserialize(&partial.proof)?,
+                public_key,
+            )?;
+            elements.push(G::deserialize_elem(&partial.evaluated_element)?);
+        }
+        let indices: Vec<u16> = partial_evaluations.iter().map(|e| e.index).collect();
+        let combined = interpolate::<G>(suite, &indices, &elements)?;
+
+        // The client state is the blind followed by the blinded element.
+        let blind = G::deserialize_scalar(&client_state[..<G as Group>::ScalarLen::USIZE])?;
+        let unblinded_element = G::serialize_elem(combined * G::invert_scalar(blind));
+        finalize_hash::<<CS as CipherSuite>::Hash>(&input, &unblinded_element)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oprf::{oprf_generate_key_pair, oprf_server_evaluate};
+
+    const SUITES: [OprfSuite; 2] = [OprfSuite::P256Sha256, OprfSuite::Ristretto255Sha512];
+
+    fn public_keys(key: &ThresholdOprfKey) -> Vec<Vec<u8>> {
+        key.shares
+            .iter()
+            .map(|share| share.public_key.clone())
+            .collect()
+    }
+
+    fn evaluate_with(
+        suite: OprfSuite,
+        key: &ThresholdOprfKey,
+        indices: &[u16],
+        input: &[u8],
+    ) -> Result<Vec<u8>, OprfError> {
+        let blind = threshold_oprf_client_blind(suite, input.to_vec()).unwrap();
+        let partials = indices
+            .iter()
+            .map(|&index| {
+                threshold_oprf_server_evaluate(
+                    suite,
+                    key.shares[index as usize - 1].clone(),
+                    blind.blinded_element.clone(),
+                )
+                .unwrap()
+            })
+            .collect();
+        threshold_oprf_client_finalize(
+            suite,
+            blind.client_state,
+            input.to_vec(),
+            key.threshold,
+            partials,
+            public_keys(key),
+        )
+    }
+
+    #[test]
+    fn test_any_threshold_subset_gives_the_full_key_output() {
+        for suite in SUITES {
+            let key_pair = oprf_generate_key_pair(suite).unwrap();
+            let key = threshold_oprf_split_key(suite, key_pair.private_key.clone(), 2, 3).unwrap();
+            assert_eq!(key.public_key, key_pair.public_key);
+            assert_eq!(key.shares.len(), 3);
+
+            let expected = oprf_server_evaluate(
+                suite,
+                OprfMode::Voprf,
+                key_pair.private_key,
+                b"input".to_vec(),
+                None,
+            )
+            .unwrap();
+            for indices in [&[1, 2][..], &[1, 3], &[3, 2], &[1, 2, 3]] {
+                assert_eq!(
+                    evaluate_with(suite, &key, indices, b"input").unwrap(),
+                    expected,
+                    "{:?} {:?}",
+                    suite,
+                    indices
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_one_of_one_and_generated_keys() {
+        for suite in SUITES {
+            let key_pair = oprf_generate_key_pair(suite).unwrap();
+            let key = threshold_oprf_split_key(suite, key_pair.private_key.clone(), 1, 1).unwrap();
+            assert_eq!(key.shares[0].private_key, key_pair.private_key);
+
+            let key = threshold_oprf_generate_key(suite, 3, 5).unwrap();
+            assert_eq!(
+                evaluate_with(suite, &key, &[5, 1, 3], b"input").unwrap(),
+                evaluate_with(suite, &key, &[2, 3, 4], b"input").unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_too_few_or_repeated_partials_are_rejected() {
+        let suite = OprfSuite::P256Sha256;
+        let key = threshold_oprf_generate_key(suite, 2, 3).unwrap();
+        assert!(matches!(
+            evaluate_with(suite, &key, &[2], b"input"),
+            Err(OprfError::InvalidParameter(_))
+        ));
+        assert!(matches!(
+            evaluate_with(suite, &key, &[2, 2], b"input"),
+            Err(OprfError::InvalidParameter(_))
+        ));
+        assert!(matches!(
+            threshold_oprf_generate_key(suite, 4, 3),
+            Err(OprfError::InvalidParameter(_))
+        ));
+        assert!(matches!(
+            threshold_oprf_generate_key(suite, 0, 3),
+            Err(OprfError::InvalidParameter(_))
+        ));
+    }
+
+    #[test]
+    fn test_partial_evaluation_from_wrong_share_fails_verification() {
+        for suite in SUITES {
+            let key = threshold_oprf_generate_key(suite, 2, 3).unwrap();
+            let blind = threshold_oprf_client_blind(suite, b"input".to_vec()).unwrap();
+            let partial_1 = threshold_oprf_server_evaluate(
+                suite,
+                key.shares[0].clone(),
+                blind.blinded_element.clone(),
+            )
+            .unwrap();
+            // Share 3 claims to be share 2.
+            let mut partial_2 = threshold_oprf_server_evaluate(
+                suite,
+                key.shares[2].clone(),
+                blind.blinded_element.clone(),
+            )
+            .unwrap();
+            partial_2.index = 2;
+
+            assert!(matches!(
+                threshold_oprf_client_finalize(
+                    suite,
+                    blind.client_state,
+                    b"input".to_vec(),
+                    key.threshold,
+                    vec![partial_1, partial_2],
+                    public_keys(&key),
+                ),
+                Err(OprfError::ProofVerification)
+            ));
+        }
+    }
+}